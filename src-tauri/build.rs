@@ -1,3 +1,26 @@
 fn main() {
-    tauri_build::build()
+    #[cfg(feature = "app")]
+    tauri_build::build();
+
+    generate_c_header();
+}
+
+// 为 src/ffi.rs 中的 C 接口生成 include/authorization.h
+fn generate_c_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("cbindgen.toml 格式错误");
+    match cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/authorization.h", crate_dir));
+        }
+        Err(e) => println!("cargo:warning=生成 C 头文件失败: {}", e),
+    }
 }
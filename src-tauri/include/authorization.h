@@ -0,0 +1,224 @@
+#ifndef AUTHORIZATION_H
+#define AUTHORIZATION_H
+
+/* 此文件由 build.rs 通过 cbindgen 生成，请勿手动修改 */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * 接口返回码：0 为成功，1..=99 与校验错误类别一一对应，100 起为调用错误
+ */
+typedef enum AuthStatus {
+  AUTH_STATUS_OK = 0,
+  AUTH_STATUS_MISSING_FILE = 1,
+  AUTH_STATUS_KEY_UNAVAILABLE = 2,
+  AUTH_STATUS_BAD_ENCODING = 3,
+  AUTH_STATUS_WRONG_LENGTH = 4,
+  AUTH_STATUS_WRONG_KEY = 5,
+  AUTH_STATUS_TAMPERED = 6,
+  AUTH_STATUS_DEVICE_MISMATCH = 7,
+  AUTH_STATUS_BINDING_MISMATCH = 8,
+  AUTH_STATUS_NOT_YET_VALID = 9,
+  AUTH_STATUS_EXPIRED = 10,
+  AUTH_STATUS_FINGERPRINT_MISMATCH = 11,
+  AUTH_STATUS_INVALID_DEVICE_CODE = 12,
+  AUTH_STATUS_REISSUE_REQUIRED = 13,
+  AUTH_STATUS_INVALID_ARGUMENT = 100,
+  AUTH_STATUS_INTERNAL = 101,
+} AuthStatus;
+
+/**
+ * 校验通过的授权 (不透明句柄)，用 auth_license_free 释放
+ */
+typedef struct AuthLicense AuthLicense;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * 返回 C 接口版本，接口有不兼容修改时递增；调用方应与自己适配的版本比对，不一致时拒绝使用
+ */
+uint32_t auth_abi_version(void);
+
+/**
+ * 校验内存中的授权文件内容。
+ *
+ * device_code 为客户端设备码，verifier_keys_json 为 verifier_keys.json 的内容，
+ * product_id 可为 NULL (使用默认产品标识)。clock_skew_secs 为校验有效期时允许的时钟偏差，
+ * 取值 0..=86400 秒，传负数使用默认值 (300 秒)。授权记录了硬件指纹时与本机硬件比对。
+ * 成功时 *out_license 指向新分配的授权句柄。
+ *
+ * # Safety
+ * license_data 须指向至少 license_len 字节的可读内存；字符串参数须为以 NUL 结尾的 UTF-8；
+ * out_license 须为有效的可写指针。
+ */
+enum AuthStatus auth_verify_buffer(const uint8_t *license_data,
+                                   size_t license_len,
+                                   const char *device_code,
+                                   const char *verifier_keys_json,
+                                   const char *product_id,
+                                   int64_t clock_skew_secs,
+                                   struct AuthLicense **out_license);
+
+/**
+ * 校验磁盘上的授权文件，参数均为文件路径 (license.lic、device_code.bin、verifier_keys.json)。
+ * product_id 与 clock_skew_secs 同 auth_verify_buffer。
+ *
+ * # Safety
+ * 字符串参数须为以 NUL 结尾的 UTF-8，product_id 可为 NULL；out_license 须为有效的可写指针。
+ */
+enum AuthStatus auth_verify_file(const char *license_path,
+                                 const char *device_code_path,
+                                 const char *verifier_keys_path,
+                                 const char *product_id,
+                                 int64_t clock_skew_secs,
+                                 struct AuthLicense **out_license);
+
+/**
+ * 返回当前线程最近一次失败的错误信息，没有时返回 NULL；须用 auth_string_free 释放
+ */
+char *auth_last_error_message(void);
+
+/**
+ * 释放本库返回的字符串
+ *
+ * # Safety
+ * s 须为本库返回且尚未释放的字符串，或 NULL。
+ */
+void auth_string_free(char *s);
+
+/**
+ * 释放授权句柄
+ *
+ * # Safety
+ * license 须为 auth_verify_* 返回且尚未释放的句柄，或 NULL。
+ */
+void auth_license_free(struct AuthLicense *license);
+
+/**
+ * 授权绑定的设备码；须用 auth_string_free 释放
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+char *auth_license_device_code(const struct AuthLicense *license);
+
+/**
+ * 授权序列号；须用 auth_string_free 释放
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+char *auth_license_serial_number(const struct AuthLicense *license);
+
+/**
+ * 产品版本 ("trial"、"standard"、"professional"、"enterprise")；须用 auth_string_free 释放
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+char *auth_license_edition(const struct AuthLicense *license);
+
+/**
+ * 签发时间 (Unix 秒)
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+int64_t auth_license_issued_at(const struct AuthLicense *license);
+
+/**
+ * 生效时间 (Unix 秒)；授权没有生效时间时返回 false
+ *
+ * # Safety
+ * license 须为有效的授权句柄，out_secs 须为有效的可写指针。
+ */
+bool auth_license_not_before(const struct AuthLicense *license, int64_t *out_secs);
+
+/**
+ * 到期时间 (Unix 秒)；永久授权返回 false
+ *
+ * # Safety
+ * license 须为有效的授权句柄，out_secs 须为有效的可写指针。
+ */
+bool auth_license_expires_at(const struct AuthLicense *license, int64_t *out_secs);
+
+/**
+ * 功能是否开启 (开关为 true 或数量上限大于 0)
+ *
+ * # Safety
+ * license 须为有效的授权句柄，name 须为以 NUL 结尾的 UTF-8。
+ */
+bool auth_license_has_feature(const struct AuthLicense *license, const char *name);
+
+/**
+ * 数量类功能的上限；功能不存在或不是数量类时返回 false
+ *
+ * # Safety
+ * license 须为有效的授权句柄，name 须为以 NUL 结尾的 UTF-8，out_limit 须为有效的可写指针。
+ */
+bool auth_license_feature_limit(const struct AuthLicense *license,
+                                const char *name,
+                                uint64_t *out_limit);
+
+/**
+ * 完整授权内容的 JSON；须用 auth_string_free 释放
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+char *auth_license_to_json(const struct AuthLicense *license);
+
+/**
+ * 授权回写的申请编号 (按授权申请文件签发时才有)，没有时返回 NULL；须用 auth_string_free 释放
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+char *auth_license_request_nonce(const struct AuthLicense *license);
+
+/**
+ * 硬件指纹比对中已变化的组件名，以逗号分隔，全部匹配时为空字符串；
+ * 授权没有记录硬件指纹时返回 NULL。须用 auth_string_free 释放
+ *
+ * # Safety
+ * license 须为有效的授权句柄。
+ */
+char *auth_license_fingerprint_drifted(const struct AuthLicense *license);
+
+/**
+ * 把设备码 (如 device_code.bin 的内容) 转换为给人读写的 XXXX-XXXX-XXXX-XXXX-C 形式，
+ * 客户端应按该形式显示设备码，授权端只接受带校验位的设备码。
+ * 成功时 *out_display 须用 auth_string_free 释放
+ *
+ * # Safety
+ * device_code 须为以 NUL 结尾的 UTF-8；out_display 须为有效的可写指针。
+ */
+enum AuthStatus auth_device_code_display(const char *device_code,
+                                         char **out_display);
+
+/**
+ * 读取本机硬件信息生成授权申请文件，写入 output_path (通常为 license_request.req)。
+ *
+ * customer_reference 可为 NULL。out_nonce 可为 NULL；不为 NULL 时写入申请编号，
+ * 须用 auth_string_free 释放，收到授权后用 auth_license_request_nonce 核对。
+ *
+ * # Safety
+ * 字符串参数须为以 NUL 结尾的 UTF-8；out_nonce 须为 NULL 或有效的可写指针。
+ */
+enum AuthStatus auth_license_request_create(const char *product_id,
+                                            const char *client_version,
+                                            const char *customer_reference,
+                                            const char *output_path,
+                                            char **out_nonce);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AUTHORIZATION_H */
@@ -0,0 +1,76 @@
+// Android 授权流程依赖的 ADB 操作。授权端使用连接 ADB 服务器的实现，
+// 测试使用 mock_adb_backend 中的内存模拟设备
+use serde::{Deserialize, Serialize};
+
+use crate::adb_client::{shell_quote, AdbDevice, TransferProgress};
+use crate::android_auth::remote_join;
+use crate::kdf::random_bytes;
+
+pub trait AdbBackend: Send + Sync {
+    // 全部设备，包括未授权、离线等状态
+    fn devices(&self) -> Result<Vec<AdbDevice>, String>;
+
+    fn pull(
+        &self,
+        serial: &str,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<Vec<u8>, String>;
+
+    // remote_path 为完整的远端文件路径
+    fn push(
+        &self,
+        serial: &str,
+        data: &[u8],
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<(), String>;
+
+    fn shell(&self, serial: &str, command: &str) -> Result<String, String>;
+
+    // 重命名远端文件，目标已存在时替换
+    fn rename(&self, serial: &str, from: &str, to: &str) -> Result<(), String>;
+}
+
+// 推送方式。Atomic 先推送到同目录下的临时文件再 mv 为目标文件名，
+// 传输中断 (如拔线) 时设备上不会留下写了一半的目标文件
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PushMode {
+    Direct,
+    #[default]
+    Atomic,
+}
+
+// 把 data 推送为 remote_dir 下的 file_name，返回目标文件的完整路径
+pub fn push_file(
+    backend: &dyn AdbBackend,
+    serial: &str,
+    data: &[u8],
+    remote_dir: &str,
+    file_name: &str,
+    mode: PushMode,
+    progress: &mut dyn FnMut(TransferProgress),
+) -> Result<String, String> {
+    if file_name.is_empty() || file_name == "." || file_name == ".." || file_name.contains('/') {
+        return Err(format!("远端文件名无效: \"{}\"", file_name));
+    }
+    let remote_path = remote_join(remote_dir, file_name);
+    if mode == PushMode::Direct {
+        backend.push(serial, data, &remote_path, progress)?;
+        return Ok(remote_path);
+    }
+    // 临时文件与目标在同一目录 (同一文件系统)，mv 才是原子的重命名
+    let temp_path = remote_join(
+        remote_dir,
+        &format!(".{}.{}.tmp", file_name, hex::encode(random_bytes::<4>()?)),
+    );
+    let result = backend
+        .push(serial, data, &temp_path, progress)
+        .and_then(|_| backend.rename(serial, &temp_path, &remote_path));
+    if result.is_err() {
+        // 尽力清理临时文件，设备已断开时只能留给下次覆盖
+        let _ = backend.shell(serial, &format!("rm -f {}", shell_quote(&temp_path)));
+    }
+    result.map(|_| remote_path)
+}
@@ -0,0 +1,728 @@
+// ADB 主机协议客户端：直接连接本机 ADB 服务器 (默认 TCP 5037)，不再为每次操作启动 adb 进程
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_ADB_SERVER_ADDR: &str = "127.0.0.1:5037";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const TRACK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const SYNC_DATA_MAX: usize = 64 * 1024; // 单个 DATA 块的最大长度 (协议限制)
+const SYNC_PATH_MAX: usize = 1024;
+const DEFAULT_FILE_MODE: u32 = 0o100644; // 推送的文件为普通文件 rw-r--r--
+const SHELL_OK_MARKER: &str = "__adb_ok__";
+
+// adb devices -l 报告的设备状态
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdbDeviceState {
+    Device,       // 已连接且已允许调试，可以操作
+    Unauthorized, // 设备上尚未确认 "允许 USB 调试"
+    Authorizing,
+    Offline,
+    Connecting,
+    Recovery,
+    Sideload,
+    Bootloader,
+    NoPermissions, // 主机没有访问 USB 设备的权限 (Linux udev 规则)
+    Unknown,
+}
+
+impl AdbDeviceState {
+    fn parse(state: &str) -> Self {
+        match state {
+            "device" => Self::Device,
+            "unauthorized" => Self::Unauthorized,
+            "authorizing" => Self::Authorizing,
+            "offline" => Self::Offline,
+            "connecting" => Self::Connecting,
+            "recovery" => Self::Recovery,
+            "sideload" => Self::Sideload,
+            "bootloader" => Self::Bootloader,
+            s if s.starts_with("no permissions") => Self::NoPermissions,
+            _ => Self::Unknown,
+        }
+    }
+
+    // 设备不可用时给操作员的处理建议
+    pub fn guidance(&self) -> Option<&'static str> {
+        match self {
+            Self::Device => None,
+            Self::Unauthorized | Self::Authorizing => Some("请戴上头显，在 \"允许 USB 调试吗？\" 对话框中勾选 \"始终允许\" 并点击允许，然后刷新设备列表"),
+            Self::Offline | Self::Connecting => Some("设备连接未就绪，请重新插拔数据线或重启头显后刷新设备列表"),
+            Self::Recovery | Self::Sideload | Self::Bootloader => Some("设备处于恢复/刷机模式，请正常启动头显后再授权"),
+            Self::NoPermissions => Some("当前用户没有访问该 USB 设备的权限，请检查 udev 规则或以管理员身份运行"),
+            Self::Unknown => Some("无法识别的设备状态，请重新连接设备"),
+        }
+    }
+}
+
+// adb devices -l 中的一台设备
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AdbDevice {
+    pub serial: String,
+    pub state: AdbDeviceState,
+    pub state_text: String, // adb 输出的原始状态，便于排查
+    pub model: Option<String>,
+    pub product: Option<String>,
+    pub device: Option<String>,
+    pub transport_id: Option<u32>,
+    pub usb: Option<String>, // USB 端口路径，网络连接的设备没有
+    pub network: bool,       // 通过 Wi-Fi (adb connect 或无线调试) 连接
+    pub guidance: Option<String>,
+}
+
+impl AdbDevice {
+    pub fn is_ready(&self) -> bool {
+        self.state == AdbDeviceState::Device
+    }
+
+    // 面向操作员的简短描述，例如 "Quest_3 (2G0YC5ZF9J0123)"
+    pub fn label(&self) -> String {
+        match &self.model {
+            Some(model) => format!("{} ({})", model, self.serial),
+            None => self.serial.clone(),
+        }
+    }
+}
+
+const DEVICE_DETAIL_KEYS: &[&str] = &["usb", "product", "model", "device", "transport_id"];
+
+// 解析 adb devices -l 的输出。每行为 "序列号 状态 [键:值 ...]"，
+// 状态可能包含空格 (如 "no permissions (...)")，因此以第一个已知的键:值字段作为状态结束
+pub(crate) fn parse_devices_long(output: &str) -> Vec<AdbDevice> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty() && !line.starts_with("List of devices") && !line.starts_with('*')
+        })
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let serial = tokens.next()?.to_string();
+            let mut state_words = Vec::new();
+            let mut in_state = true;
+            let mut device = AdbDevice {
+                serial,
+                state: AdbDeviceState::Unknown,
+                state_text: String::new(),
+                model: None,
+                product: None,
+                device: None,
+                transport_id: None,
+                usb: None,
+                network: false,
+                guidance: None,
+            };
+            for token in tokens {
+                match token.split_once(':') {
+                    Some((key, value)) if DEVICE_DETAIL_KEYS.contains(&key) => {
+                        in_state = false;
+                        match key {
+                            "usb" => device.usb = Some(value.to_string()),
+                            "product" => device.product = Some(value.to_string()),
+                            "model" => device.model = Some(value.to_string()),
+                            "device" => device.device = Some(value.to_string()),
+                            _ => device.transport_id = value.parse().ok(),
+                        }
+                    }
+                    _ if in_state => state_words.push(token),
+                    _ => {}
+                }
+            }
+            device.state_text = state_words.join(" ");
+            device.state = AdbDeviceState::parse(&device.state_text);
+            device.network = device.usb.is_none() && is_network_serial(&device.serial);
+            device.guidance = match device.state {
+                AdbDeviceState::Offline | AdbDeviceState::Connecting if device.network => {
+                    Some("无线连接未就绪，请确认头显与电脑在同一网络，断开后重新连接".to_string())
+                }
+                state => state.guidance().map(str::to_string),
+            };
+            Some(device)
+        })
+        .collect()
+}
+
+// 网络设备的序列号为 "主机:端口"，或无线调试自动连接时的 mDNS 服务名
+fn is_network_serial(serial: &str) -> bool {
+    serial.contains("._adb-tls-connect.")
+        || serial
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+// 解析并规范化 "主机:端口"。省略端口时使用 default_port，为 None 时端口必填
+// (无线调试的配对端口每次随机，不能省略)
+pub fn parse_network_address(input: &str, default_port: Option<u16>) -> Result<String, String> {
+    let input = input.trim();
+    let (host, port) = match input.rsplit_once(':') {
+        // 不带方括号的 IPv6 地址无法区分端口，要求写成 [::1]:5555
+        Some((host, port)) if !host.contains(':') || host.ends_with(']') => (host, Some(port)),
+        _ => (input, None),
+    };
+    if host.is_empty()
+        || host.chars().any(char::is_whitespace)
+        || (port.is_none() && host.contains(':'))
+    {
+        return Err(format!("设备地址无效: \"{}\"", input));
+    }
+    let port = match (port, default_port) {
+        (Some(port), _) => port
+            .parse::<u16>()
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| format!("设备端口无效: \"{}\"", port))?,
+        (None, Some(port)) => port,
+        (None, None) => return Err(format!("设备地址 \"{}\" 缺少端口", input)),
+    };
+    Ok(format!("{}:{}", host, port))
+}
+
+// 无线调试配对码为头显上显示的 6 位数字
+pub fn validate_pairing_code(code: &str) -> Result<&str, String> {
+    let code = code.trim();
+    if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+        Ok(code)
+    } else {
+        Err("配对码应为头显上显示的 6 位数字".to_string())
+    }
+}
+
+// host:mdns:services 发现的无线调试服务
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MdnsService {
+    pub name: String,
+    pub service_type: String, // _adb-tls-pairing._tcp (等待配对) 或 _adb-tls-connect._tcp (可连接)
+    pub address: String,
+}
+
+impl MdnsService {
+    pub fn is_pairing(&self) -> bool {
+        self.service_type.starts_with("_adb-tls-pairing.")
+    }
+}
+
+// 每行为 "服务名\t服务类型\t地址:端口"
+fn parse_mdns_services(output: &str) -> Vec<MdnsService> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let service = MdnsService {
+                name: fields.next()?.to_string(),
+                service_type: fields.next()?.to_string(),
+                address: fields.next()?.to_string(),
+            };
+            (!service.name.is_empty()).then_some(service)
+        })
+        .collect()
+}
+
+// 传输进度；total 为文件总大小，远端文件大小未知时为 None
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub transferred: u64,
+    pub total: Option<u64>,
+}
+
+// sync STAT 返回的远端文件信息
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteStat {
+    pub mode: u32,
+    pub size: u32,
+    pub mtime: u32,
+}
+
+impl RemoteStat {
+    pub fn is_file(&self) -> bool {
+        self.mode & 0o170000 == 0o100000
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AdbClient {
+    addr: String,
+    timeout: Duration,
+}
+
+impl Default for AdbClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_ADB_SERVER_ADDR)
+    }
+}
+
+impl AdbClient {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.addr
+    }
+
+    // 能连上说明服务器已启动；未启动时由调用方用 adb start-server 拉起
+    pub fn is_server_running(&self) -> bool {
+        self.connect().is_ok()
+    }
+
+    // host:version，返回服务器协议版本 (如 41)
+    pub fn server_version(&self) -> Result<u32, String> {
+        let mut stream = self.host_request("host:version")?;
+        let version = read_length_prefixed(&mut stream)?;
+        u32::from_str_radix(version.trim(), 16)
+            .map_err(|_| format!("ADB 服务器返回的版本号无效: {}", version))
+    }
+
+    // host:devices-l，返回全部设备 (包括未授权、离线等状态)
+    pub fn devices(&self) -> Result<Vec<AdbDevice>, String> {
+        let mut stream = self.host_request("host:devices-l")?;
+        let listing = read_length_prefixed(&mut stream)?;
+        Ok(parse_devices_long(&listing))
+    }
+
+    // host:track-devices-l：连接后先收到当前设备列表，之后每次设备变化收到一份完整列表。
+    // stop 置位后返回 Ok；服务器断开或出错时返回 Err，由调用方决定是否重连
+    pub fn track_devices(
+        &self,
+        stop: &AtomicBool,
+        on_update: &mut dyn FnMut(Vec<AdbDevice>),
+    ) -> Result<(), String> {
+        let mut stream = self.host_request("host:track-devices-l")?;
+        // 设备没有变化时服务器不发送任何数据，定期醒来检查 stop
+        stream
+            .set_read_timeout(Some(TRACK_POLL_INTERVAL))
+            .map_err(|e| format!("设置 ADB 连接超时失败: {}", e))?;
+        let mut len_hex = [0u8; 4];
+        let mut filled = 0;
+        while !stop.load(Ordering::Relaxed) {
+            match stream.read(&mut len_hex[filled..]) {
+                Ok(0) => return Err("ADB 服务器已断开设备监听连接".to_string()),
+                Ok(n) => filled += n,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    continue
+                }
+                Err(e) => return Err(format!("读取设备变化失败: {}", e)),
+            }
+            if filled < len_hex.len() {
+                continue;
+            }
+            filled = 0;
+            let len = std::str::from_utf8(&len_hex)
+                .ok()
+                .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+                .ok_or("ADB 响应长度无效")?;
+            let mut listing = vec![0u8; len];
+            stream
+                .set_read_timeout(Some(self.timeout))
+                .and_then(|_| stream.read_exact(&mut listing))
+                .and_then(|_| stream.set_read_timeout(Some(TRACK_POLL_INTERVAL)))
+                .map_err(|e| format!("读取设备变化失败: {}", e))?;
+            on_update(parse_devices_long(&String::from_utf8_lossy(&listing)));
+        }
+        Ok(())
+    }
+
+    // host:connect：连接 Wi-Fi 设备 (adb connect)。服务器总是返回 OKAY，
+    // 是否成功要看返回的文字，例如 "connected to ..." 或 "failed to connect to ..."
+    pub fn connect_device(&self, address: &str) -> Result<String, String> {
+        let message = self.host_query(&format!("host:connect:{}", address))?;
+        if message.starts_with("connected to") || message.starts_with("already connected to") {
+            Ok(message)
+        } else {
+            Err(message)
+        }
+    }
+
+    // host:disconnect：断开 Wi-Fi 设备；设备不存在时服务器返回 FAIL
+    pub fn disconnect_device(&self, address: &str) -> Result<String, String> {
+        self.host_query(&format!("host:disconnect:{}", address))
+    }
+
+    // host:pair：用配对码与开启了无线调试的设备配对 (adb pair)，配对后仍需 connect_device
+    pub fn pair_device(&self, address: &str, pairing_code: &str) -> Result<String, String> {
+        let message = self.host_query(&format!("host:pair:{}:{}", pairing_code, address))?;
+        if message.starts_with("Successfully paired") {
+            Ok(message)
+        } else {
+            Err(message)
+        }
+    }
+
+    // host:mdns:services，返回局域网中通过 mDNS 发现的无线调试设备
+    pub fn mdns_services(&self) -> Result<Vec<MdnsService>, String> {
+        Ok(parse_mdns_services(&self.host_query("host:mdns:services")?))
+    }
+
+    pub fn kill_server(&self) -> Result<(), String> {
+        self.host_request("host:kill").map(|_| ())
+    }
+
+    // 在设备上执行 shell 命令并返回全部输出 (旧版 shell 协议，标准错误与标准输出合并)
+    pub fn shell(&self, serial: Option<&str>, command: &str) -> Result<String, String> {
+        let mut stream = self.open_transport(serial)?;
+        let request = format!("shell:{}", command);
+        send_request(&mut stream, &request)?;
+        read_status(&mut stream, &request)?;
+        let mut output = Vec::new();
+        stream
+            .read_to_end(&mut output)
+            .map_err(|e| format!("读取 shell 输出失败: {}", e))?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    // 在设备上用 mv 重命名文件；同一目录内的重命名是原子的，目标文件已存在时被替换。
+    // 旧版 shell 协议拿不到退出码，以命令成功后输出的标记判断结果
+    pub fn rename(&self, serial: Option<&str>, from: &str, to: &str) -> Result<(), String> {
+        let command = format!(
+            "mv -f {} {} && echo {}",
+            shell_quote(from),
+            shell_quote(to),
+            SHELL_OK_MARKER
+        );
+        let output = self.shell(serial, &command)?;
+        if output.trim_end().ends_with(SHELL_OK_MARKER) {
+            Ok(())
+        } else {
+            Err(format!("重命名 {} 为 {} 失败: {}", from, to, output.trim()))
+        }
+    }
+
+    // 查询远端文件，文件不存在时返回 None
+    pub fn stat(
+        &self,
+        serial: Option<&str>,
+        remote_path: &str,
+    ) -> Result<Option<RemoteStat>, String> {
+        let mut sync = self.open_sync(serial)?;
+        let stat = sync.stat(remote_path)?;
+        sync.quit();
+        Ok(stat)
+    }
+
+    // 拉取远端文件到本地，返回字节数；失败时删除不完整的本地文件
+    pub fn pull(
+        &self,
+        serial: Option<&str>,
+        remote_path: &str,
+        local_path: &Path,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<u64, String> {
+        let mut file = File::create(local_path)
+            .map_err(|e| format!("创建本地文件 {} 失败: {}", local_path.display(), e))?;
+        let result = self.pull_into(serial, remote_path, &mut file, progress);
+        drop(file);
+        if result.is_err() {
+            let _ = fs::remove_file(local_path);
+        }
+        result
+    }
+
+    // 拉取远端文件写入 out，返回字节数
+    pub fn pull_into(
+        &self,
+        serial: Option<&str>,
+        remote_path: &str,
+        out: &mut dyn Write,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<u64, String> {
+        let mut sync = self.open_sync(serial)?;
+        let total = match sync.stat(remote_path)? {
+            Some(stat) => Some(u64::from(stat.size)),
+            None => return Err(format!("远端文件不存在: {}", remote_path)),
+        };
+        let received = sync.recv(remote_path, out, total, progress)?;
+        sync.quit();
+        Ok(received)
+    }
+
+    // 推送本地文件到远端路径 (完整文件路径)，返回字节数
+    pub fn push(
+        &self,
+        serial: Option<&str>,
+        local_path: &Path,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<u64, String> {
+        let mut file = File::open(local_path)
+            .map_err(|e| format!("打开本地文件 {} 失败: {}", local_path.display(), e))?;
+        let total = file.metadata().map(|m| m.len()).ok();
+        self.push_from(serial, &mut file, total, remote_path, progress)
+    }
+
+    // 从 input 读取内容推送到远端路径，total 仅用于进度显示
+    pub fn push_from(
+        &self,
+        serial: Option<&str>,
+        input: &mut dyn Read,
+        total: Option<u64>,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<u64, String> {
+        let mut sync = self.open_sync(serial)?;
+        let sent = sync.send(remote_path, input, total, progress)?;
+        sync.quit();
+        Ok(sent)
+    }
+
+    fn connect(&self) -> Result<TcpStream, String> {
+        let addr = self
+            .addr
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| format!("ADB 服务器地址无效: {}", self.addr))?;
+        let stream = TcpStream::connect_timeout(&addr, self.timeout)
+            .map_err(|e| format!("无法连接 ADB 服务器 ({}): {}", self.addr, e))?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+            .map_err(|e| format!("设置 ADB 连接超时失败: {}", e))?;
+        Ok(stream)
+    }
+
+    fn host_request(&self, request: &str) -> Result<TcpStream, String> {
+        let mut stream = self.connect()?;
+        send_request(&mut stream, request)?;
+        read_status(&mut stream, request)?;
+        Ok(stream)
+    }
+
+    // 返回 OKAY 后带长度前缀的一段文字
+    fn host_query(&self, request: &str) -> Result<String, String> {
+        let mut stream = self.host_request(request)?;
+        read_length_prefixed(&mut stream).map(|message| message.trim().to_string())
+    }
+
+    // 把连接切换到指定设备，之后的请求直接发给设备上的 adbd
+    fn open_transport(&self, serial: Option<&str>) -> Result<TcpStream, String> {
+        let request = match serial {
+            Some(serial) => format!("host:transport:{}", serial),
+            None => "host:transport-any".to_string(),
+        };
+        self.host_request(&request)
+    }
+
+    fn open_sync(&self, serial: Option<&str>) -> Result<SyncConnection, String> {
+        let mut stream = self.open_transport(serial)?;
+        send_request(&mut stream, "sync:")?;
+        read_status(&mut stream, "sync:")?;
+        Ok(SyncConnection { stream })
+    }
+}
+
+// sync 子协议：请求和响应均为 4 字节 ID + 4 字节小端长度 + 数据
+struct SyncConnection {
+    stream: TcpStream,
+}
+
+impl SyncConnection {
+    fn stat(&mut self, remote_path: &str) -> Result<Option<RemoteStat>, String> {
+        self.request(b"STAT", remote_path.as_bytes())?;
+        let mut response = [0u8; 16];
+        self.stream
+            .read_exact(&mut response)
+            .map_err(|e| format!("读取 STAT 响应失败: {}", e))?;
+        if &response[..4] != b"STAT" {
+            return Err("ADB 服务器返回的 STAT 响应无效".to_string());
+        }
+        let field = |i: usize| u32::from_le_bytes(response[i..i + 4].try_into().unwrap());
+        let stat = RemoteStat {
+            mode: field(4),
+            size: field(8),
+            mtime: field(12),
+        };
+        // 文件不存在时各字段均为 0
+        Ok((stat.mode != 0).then_some(stat))
+    }
+
+    fn recv(
+        &mut self,
+        remote_path: &str,
+        out: &mut dyn Write,
+        total: Option<u64>,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<u64, String> {
+        check_sync_path(remote_path)?;
+        self.request(b"RECV", remote_path.as_bytes())?;
+        let mut transferred = 0u64;
+        let mut buffer = vec![0u8; SYNC_DATA_MAX];
+        loop {
+            let (id, len) = self.read_header()?;
+            match &id {
+                b"DATA" => {
+                    let len = len as usize;
+                    if len > SYNC_DATA_MAX {
+                        return Err(format!("ADB 数据块过大: {} 字节", len));
+                    }
+                    self.stream
+                        .read_exact(&mut buffer[..len])
+                        .map_err(|e| format!("接收文件数据失败: {}", e))?;
+                    out.write_all(&buffer[..len])
+                        .map_err(|e| format!("写入本地文件失败: {}", e))?;
+                    transferred += len as u64;
+                    progress(TransferProgress { transferred, total });
+                }
+                b"DONE" => return Ok(transferred),
+                b"FAIL" => {
+                    return Err(format!(
+                        "拉取 {} 失败: {}",
+                        remote_path,
+                        self.read_message(len)?
+                    ))
+                }
+                _ => return Err("ADB 服务器返回的 sync 响应无效".to_string()),
+            }
+        }
+    }
+
+    fn send(
+        &mut self,
+        remote_path: &str,
+        input: &mut dyn Read,
+        total: Option<u64>,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<u64, String> {
+        let target = format!("{},{}", remote_path, DEFAULT_FILE_MODE);
+        check_sync_path(&target)?;
+        self.request(b"SEND", target.as_bytes())?;
+        let mut transferred = 0u64;
+        let mut buffer = vec![0u8; SYNC_DATA_MAX];
+        loop {
+            let len = input
+                .read(&mut buffer)
+                .map_err(|e| format!("读取本地文件失败: {}", e))?;
+            if len == 0 {
+                break;
+            }
+            self.request(b"DATA", &buffer[..len])?;
+            transferred += len as u64;
+            progress(TransferProgress { transferred, total });
+        }
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .unwrap_or(0);
+        self.header(b"DONE", mtime)?;
+        let (id, len) = self.read_header()?;
+        match &id {
+            b"OKAY" => Ok(transferred),
+            b"FAIL" => Err(format!(
+                "推送到 {} 失败: {}",
+                remote_path,
+                self.read_message(len)?
+            )),
+            _ => Err("ADB 服务器返回的 sync 响应无效".to_string()),
+        }
+    }
+
+    // 结束 sync 会话；失败不影响已完成的传输
+    fn quit(&mut self) {
+        let _ = self.header(b"QUIT", 0);
+    }
+
+    fn request(&mut self, id: &[u8; 4], data: &[u8]) -> Result<(), String> {
+        self.header(id, data.len() as u32)?;
+        self.stream
+            .write_all(data)
+            .map_err(|e| format!("发送 sync 数据失败: {}", e))
+    }
+
+    fn header(&mut self, id: &[u8; 4], value: u32) -> Result<(), String> {
+        let mut header = [0u8; 8];
+        header[..4].copy_from_slice(id);
+        header[4..].copy_from_slice(&value.to_le_bytes());
+        self.stream
+            .write_all(&header)
+            .map_err(|e| format!("发送 sync 请求失败: {}", e))
+    }
+
+    fn read_header(&mut self) -> Result<([u8; 4], u32), String> {
+        let mut header = [0u8; 8];
+        self.stream
+            .read_exact(&mut header)
+            .map_err(|e| format!("读取 sync 响应失败: {}", e))?;
+        let id = header[..4].try_into().unwrap();
+        let len = u32::from_le_bytes(header[4..].try_into().unwrap());
+        Ok((id, len))
+    }
+
+    fn read_message(&mut self, len: u32) -> Result<String, String> {
+        let mut message = vec![0u8; len.min(SYNC_DATA_MAX as u32) as usize];
+        self.stream
+            .read_exact(&mut message)
+            .map_err(|e| format!("读取错误信息失败: {}", e))?;
+        Ok(String::from_utf8_lossy(&message).into_owned())
+    }
+}
+
+fn check_sync_path(path: &str) -> Result<(), String> {
+    if path.len() > SYNC_PATH_MAX {
+        return Err(format!("远端路径过长 ({} 字节): {}", path.len(), path));
+    }
+    Ok(())
+}
+
+// 用单引号包裹 shell 参数，参数中的单引号写成 '\''
+pub(crate) fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+// 主机请求：4 位十六进制长度 + 请求内容
+fn send_request(stream: &mut TcpStream, request: &str) -> Result<(), String> {
+    if request.len() > 0xffff {
+        return Err(format!("ADB 请求过长: {} 字节", request.len()));
+    }
+    stream
+        .write_all(format!("{:04x}{}", request.len(), request).as_bytes())
+        .map_err(|e| format!("发送 ADB 请求 {} 失败: {}", request, e))
+}
+
+// 主机响应：OKAY，或 FAIL + 带长度前缀的错误信息
+fn read_status(stream: &mut TcpStream, request: &str) -> Result<(), String> {
+    let mut status = [0u8; 4];
+    stream
+        .read_exact(&mut status)
+        .map_err(|e| format!("读取 ADB 响应失败 ({}): {}", request, e))?;
+    match &status {
+        b"OKAY" => Ok(()),
+        b"FAIL" => Err(format!(
+            "ADB 服务器拒绝请求 {}: {}",
+            request,
+            read_length_prefixed(stream)?
+        )),
+        _ => Err(format!(
+            "ADB 服务器响应无效 ({}): {:?}",
+            request,
+            String::from_utf8_lossy(&status)
+        )),
+    }
+}
+
+fn read_length_prefixed(stream: &mut TcpStream) -> Result<String, String> {
+    let mut len_hex = [0u8; 4];
+    stream
+        .read_exact(&mut len_hex)
+        .map_err(|e| format!("读取 ADB 响应长度失败: {}", e))?;
+    let len = std::str::from_utf8(&len_hex)
+        .ok()
+        .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+        .ok_or("ADB 响应长度无效")?;
+    let mut payload = vec![0u8; len];
+    stream
+        .read_exact(&mut payload)
+        .map_err(|e| format!("读取 ADB 响应失败: {}", e))?;
+    Ok(String::from_utf8_lossy(&payload).into_owned())
+}
@@ -1,87 +1,140 @@
 use std::process::Command as StdCommand; // 为了区分
 use tauri::async_runtime::spawn_blocking;
 
+use crate::adb_backend::AdbBackend;
+use crate::adb_client::{AdbClient, AdbDevice, MdnsService, TransferProgress};
 
-// 为Tauri命令或异步函数包装adb操作
-pub async fn adb_pull_cmd(adb_path: &str, device_id: Option<&str>, remote_path: &str, local_path: &str) -> Result<String, String> {
-    let adb_path = adb_path.to_string();
-    let remote_path = remote_path.to_string();
-    let local_path = local_path.to_string();
-    let device_args = device_id.map(|id| vec!["-s".to_string(), id.to_string()]).unwrap_or_default();
+// 通过 ADB 主机协议连接本机 ADB 服务器；服务器未启动时用随附的 adb 拉起一次
+pub(crate) fn connect_client(adb_path: &str) -> Result<AdbClient, String> {
+    let client = AdbClient::default();
+    if client.is_server_running() {
+        return Ok(client);
+    }
+    let output = StdCommand::new(adb_path).arg("start-server").output()
+        .map_err(|e| format!("执行 adb start-server 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("启动 ADB 服务失败: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    if !client.is_server_running() {
+        return Err(format!("ADB 服务已启动，但无法连接 {}", client.address()));
+    }
+    Ok(client)
+}
 
-    spawn_blocking(move || {
-        let mut cmd = StdCommand::new(&adb_path);
-        if !device_args.is_empty() {
-            cmd.args(&device_args);
-        }
-        let output = cmd.args(&["pull", &remote_path, &local_path])
-            .output()
-            .map_err(|e| format!("执行 adb pull 失败: {}", e))?;
-
-        if output.status.success() {
-            Ok(format!("文件拉取成功: {}", local_path))
-        } else {
-            Err(format!("文件拉取失败 ({}): {}", local_path, String::from_utf8_lossy(&output.stderr)))
-        }
-    }).await.map_err(|e| format!("adb_pull spawn_blocking error: {}", e))?
+// 授权流程使用的 ADB 实现：通过主机协议连接本机 ADB 服务器
+pub struct AdbServerBackend {
+    adb_path: String, // 服务器未启动时用于 adb start-server
+}
+
+impl AdbServerBackend {
+    pub fn new(adb_path: String) -> Self {
+        Self { adb_path }
+    }
 }
 
-pub async fn adb_push_cmd(adb_path: &str, device_id: Option<&str>, local_path: &str, remote_path: &str) -> Result<String, String> {
+impl AdbBackend for AdbServerBackend {
+    fn devices(&self) -> Result<Vec<AdbDevice>, String> {
+        connect_client(&self.adb_path)?.devices()
+    }
+
+    fn pull(&self, serial: &str, remote_path: &str, progress: &mut dyn FnMut(TransferProgress)) -> Result<Vec<u8>, String> {
+        let mut content = Vec::new();
+        connect_client(&self.adb_path)?.pull_into(Some(serial), remote_path, &mut content, progress)?;
+        Ok(content)
+    }
+
+    fn push(&self, serial: &str, data: &[u8], remote_path: &str, progress: &mut dyn FnMut(TransferProgress)) -> Result<(), String> {
+        let mut input = data;
+        connect_client(&self.adb_path)?
+            .push_from(Some(serial), &mut input, Some(data.len() as u64), remote_path, progress)
+            .map(|_| ())
+    }
+
+    fn shell(&self, serial: &str, command: &str) -> Result<String, String> {
+        connect_client(&self.adb_path)?.shell(Some(serial), command)
+    }
+
+    fn rename(&self, serial: &str, from: &str, to: &str) -> Result<(), String> {
+        connect_client(&self.adb_path)?.rename(Some(serial), from, to)
+    }
+}
+
+// 返回全部设备 (包括未授权、离线等状态)，调用方按 is_ready 过滤
+pub async fn adb_devices_cmd(adb_path: &str) -> Result<Vec<AdbDevice>, String> {
     let adb_path = adb_path.to_string();
-    let local_path = local_path.to_string();
-    let remote_path = remote_path.to_string() + "/license.lic";
-    let device_args = device_id.map(|id| vec!["-s".to_string(), id.to_string()]).unwrap_or_default();
+    spawn_blocking(move || {
+        AdbServerBackend::new(adb_path).devices()
+            .map_err(|e| format!("获取设备列表失败: {}", e))
+    }).await.map_err(|e| format!("adb_devices spawn_blocking error: {}", e))?
+}
 
+// 无线调试默认端口为 5555 (adb tcpip 5555)
+pub const DEFAULT_WIRELESS_PORT: u16 = 5555;
 
+// adb connect
+pub async fn adb_connect_cmd(adb_path: &str, address: &str) -> Result<String, String> {
+    let adb_path = adb_path.to_string();
+    let address = address.to_string();
     spawn_blocking(move || {
-        let mut cmd = StdCommand::new(&adb_path);
-        if !device_args.is_empty() {
-            cmd.args(&device_args);
-        }
-        let output = cmd.args(&["push", &local_path, &remote_path])
-            .output()
-            .map_err(|e| format!("执行 adb push 失败: {}", e))?;
-
-        if output.status.success() {
-            Ok(format!("文件推送成功: {}", remote_path))
-        } else {
-            Err(format!("文件推送失败 ({}): {}", remote_path, String::from_utf8_lossy(&output.stderr)))
-        }
-    }).await.map_err(|e| format!("adb_push spawn_blocking error: {}",e))?
+        connect_client(&adb_path)?.connect_device(&address)
+            .map_err(|e| format!("连接 {} 失败: {}", address, e))
+    }).await.map_err(|e| format!("adb_connect spawn_blocking error: {}", e))?
 }
 
-pub async fn adb_devices_cmd(adb_path: &str) -> Result<Vec<String>, String> {
+// adb disconnect
+pub async fn adb_disconnect_cmd(adb_path: &str, address: &str) -> Result<String, String> {
     let adb_path = adb_path.to_string();
+    let address = address.to_string();
     spawn_blocking(move || {
-        let output = StdCommand::new(&adb_path).arg("devices").output()
-            .map_err(|e| format!("执行 adb devices 失败: {}", e))?;
-
-        if output.status.success() {
-            let devices_str = String::from_utf8_lossy(&output.stdout);
-            let device_list: Vec<String> = devices_str
-                .lines()
-                .filter_map(|line| {
-                    let trimmed_line = line.trim();
-                    if trimmed_line.ends_with("device") { // 更可靠的过滤方式
-                        trimmed_line.split_whitespace().next().map(String::from)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            Ok(device_list)
-        } else {
-            Err(format!("获取设备列表失败: {}", String::from_utf8_lossy(&output.stderr)))
-        }
-    }).await.map_err(|e| format!("adb_devices spawn_blocking error: {}", e))?
+        connect_client(&adb_path)?.disconnect_device(&address)
+            .map_err(|e| format!("断开 {} 失败: {}", address, e))
+    }).await.map_err(|e| format!("adb_disconnect spawn_blocking error: {}", e))?
 }
 
+// adb pair。较旧的 ADB 服务器不支持 host:pair，此时退回执行随附的 adb pair
+pub async fn adb_pair_cmd(adb_path: &str, address: &str, pairing_code: &str) -> Result<String, String> {
+    let adb_path = adb_path.to_string();
+    let address = address.to_string();
+    let pairing_code = pairing_code.to_string();
+    spawn_blocking(move || {
+        match connect_client(&adb_path)?.pair_device(&address, &pairing_code) {
+            Err(e) if e.contains("unknown host service") => {
+                let output = StdCommand::new(&adb_path).args(["pair", &address, &pairing_code]).output()
+                    .map_err(|e| format!("执行 adb pair 失败: {}", e))?;
+                let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if output.status.success() && stdout.starts_with("Successfully paired") {
+                    Ok(stdout)
+                } else {
+                    Err(format!("{} {}", stdout, String::from_utf8_lossy(&output.stderr).trim()))
+                }
+            }
+            result => result,
+        }.map_err(|e| format!("与 {} 配对失败: {}", address, e))
+    }).await.map_err(|e| format!("adb_pair spawn_blocking error: {}", e))?
+}
 
-pub async fn adb_kill_cmd(adb_path: &str) -> Result<String, String> {
+// 已连接的网络设备，以及通过 mDNS 发现的无线调试服务 (服务器未开启 mDNS 时为空)
+pub async fn adb_network_devices_cmd(adb_path: &str) -> Result<(Vec<AdbDevice>, Vec<MdnsService>), String> {
     let adb_path = adb_path.to_string();
     spawn_blocking(move || {
-        StdCommand::new(&adb_path).arg("kill-server").output()
-            .map_err(|e| format!("执行 adb kill-server 失败: {}", e))?;
+        let client = connect_client(&adb_path)?;
+        let devices = client.devices()
+            .map_err(|e| format!("获取设备列表失败: {}", e))?
+            .into_iter().filter(|device| device.network).collect();
+        let services = client.mdns_services().unwrap_or_default();
+        Ok((devices, services))
+    }).await.map_err(|e| format!("adb_network_devices spawn_blocking error: {}", e))?
+}
+
+// 服务器未运行时无需处理
+pub async fn adb_kill_cmd() -> Result<String, String> {
+    spawn_blocking(move || {
+        let client = AdbClient::default();
+        if !client.is_server_running() {
+            return Ok("ADB server not running.".to_string());
+        }
+        client.kill_server()
+            .map_err(|e| format!("关闭 ADB 服务失败: {}", e))?;
         Ok("ADB server killed.".to_string())
     }).await.map_err(|e| format!("adb_kill spawn_blocking error: {}", e))?
-}
\ No newline at end of file
+}
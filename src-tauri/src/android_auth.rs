@@ -0,0 +1,317 @@
+// Android 头显授权流程：拉取设备码 -> 签发 -> 推送授权文件。
+// 只依赖 AdbBackend，授权端与测试共用同一套流程
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::adb_backend::{self, AdbBackend, PushMode};
+use crate::adb_client::{AdbDevice, TransferProgress};
+use crate::device_code::DeviceCode;
+
+pub const DEVICE_CODE_FILE_NAME: &str = "device_code.bin";
+pub const LICENSE_FILE_NAME: &str = "license.lic";
+
+// 客户端在头显上保存设备码与授权文件的目录，按顺序尝试
+pub const DEFAULT_ANDROID_CLIENT_DIRS: &[&str] = &[
+    "/storage/emulated/0/Android/data/alvr.client.stable/files/BUPT-VR_Client/",
+    "/storage/emulated/0/Android/media/alvr.client.stable/files/",
+];
+
+// 批量模式下默认同时处理的设备数；USB 集线器带宽有限，过多并发反而更慢
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct AndroidAuthOptions {
+    pub batch_mode: bool, // false 时只处理第一台可用设备
+    pub remote_dirs: Vec<String>,
+    pub device_code_file: String, // 客户端目录下的文件名
+    pub license_file: String,
+    pub max_concurrency: usize, // 同时处理的设备数上限，0 视为 1
+    pub push_mode: PushMode,
+}
+
+impl Default for AndroidAuthOptions {
+    fn default() -> Self {
+        Self {
+            batch_mode: false,
+            remote_dirs: DEFAULT_ANDROID_CLIENT_DIRS
+                .iter()
+                .map(|dir| dir.to_string())
+                .collect(),
+            device_code_file: DEVICE_CODE_FILE_NAME.to_string(),
+            license_file: LICENSE_FILE_NAME.to_string(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            push_mode: PushMode::default(),
+        }
+    }
+}
+
+// 单台设备的处理结果；remote_dir 为推送了授权文件的目录。
+// 推送后回读校验未通过时 verification_error 有值，此时授权文件已在设备上但不一定可用
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceAuthOutcome {
+    pub serial: String,
+    pub label: String,
+    pub remote_dir: Option<String>,
+    pub device_code: Option<String>, // 带校验位的显示形式
+    pub error: Option<String>,
+    pub verification_error: Option<String>,
+}
+
+impl DeviceAuthOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none() && self.verification_error.is_none()
+    }
+
+    // 已推送但未通过校验
+    pub fn unverified(&self) -> bool {
+        self.error.is_none() && self.verification_error.is_some()
+    }
+
+    // 与以往返回给界面的文字保持一致
+    pub fn summary(&self) -> String {
+        match (&self.remote_dir, &self.error, &self.verification_error) {
+            (Some(dir), None, None) => format!("设备 {} @ {} 授权成功", self.serial, dir),
+            (Some(dir), None, Some(error)) => format!(
+                "设备 {} @ {} 授权文件已推送但未通过校验: {}",
+                self.serial, dir, error
+            ),
+            (_, Some(error), _) => format!("设备 {} 授权失败: {}", self.serial, error),
+            (None, None, _) => format!("设备 {} 未处理", self.serial),
+        }
+    }
+}
+
+// 流程中的日志与传输进度，由调用方转发到界面
+pub trait AndroidAuthReporter: Send + Sync {
+    fn log(&self, level: &str, message: String);
+
+    fn transfer(&self, _serial: &str, _file_name: &str, _progress: TransferProgress) {}
+}
+
+// 为设备码签发授权文件内容
+pub type LicenseIssuer<'a> = dyn Fn(&DeviceCode) -> Result<Vec<u8>, String> + Sync + 'a;
+
+// 对从设备回读的授权文件做完整校验 (与客户端相同的流程)
+pub type LicenseVerifier<'a> = dyn Fn(&[u8], &DeviceCode) -> Result<(), String> + Sync + 'a;
+
+// 处理所有 (或第一台) 可用设备；没有可用设备时返回错误
+pub fn authorize_android_devices(
+    backend: &dyn AdbBackend,
+    options: &AndroidAuthOptions,
+    issue: &LicenseIssuer,
+    verify: &LicenseVerifier,
+    reporter: &dyn AndroidAuthReporter,
+) -> Result<Vec<DeviceAuthOutcome>, String> {
+    let devices = backend.devices()?;
+    log_unready_devices(reporter, &devices);
+    let mut ready: Vec<&AdbDevice> = devices.iter().filter(|device| device.is_ready()).collect();
+    if ready.is_empty() {
+        let msg = if devices.is_empty() {
+            "未检测到设备，请连接设备后重试".to_string()
+        } else {
+            "已连接的设备均不可用 (未允许 USB 调试或离线)，请按提示处理后重试".to_string()
+        };
+        reporter.log("error", msg.clone());
+        return Err(msg);
+    }
+
+    let labels = |devices: &[&AdbDevice]| {
+        devices
+            .iter()
+            .map(|device| device.label())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if options.batch_mode {
+        reporter.log(
+            "info",
+            format!(
+                "批量模式：将处理所有已连接设备 (最多同时 {} 台): {}",
+                options.max_concurrency.max(1),
+                labels(&ready)
+            ),
+        );
+    } else {
+        // 如果不是批量模式，只取第一个设备
+        ready.truncate(1);
+        reporter.log("info", format!("单设备模式：将处理设备 {}", labels(&ready)));
+    }
+
+    Ok(authorize_concurrently(
+        &ready,
+        options.max_concurrency,
+        |device| authorize_device(backend, options, issue, verify, reporter, device),
+    ))
+}
+
+// 由不超过 max_concurrency 个工作线程依次领取设备处理；
+// 每台设备的数据只在各自的调用中传递，结果按设备原顺序返回
+fn authorize_concurrently(
+    devices: &[&AdbDevice],
+    max_concurrency: usize,
+    authorize: impl Fn(&AdbDevice) -> DeviceAuthOutcome + Sync,
+) -> Vec<DeviceAuthOutcome> {
+    let workers = max_concurrency.clamp(1, devices.len().max(1));
+    if workers == 1 {
+        return devices.iter().map(|device| authorize(device)).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; devices.len()]);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(device) = devices.get(index) else {
+                    break;
+                };
+                let outcome = authorize(device);
+                results.lock().unwrap()[index] = Some(outcome);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+// 依次尝试各个客户端目录，某个目录推送成功即结束 (回读校验失败也不再尝试其他目录)
+pub fn authorize_device(
+    backend: &dyn AdbBackend,
+    options: &AndroidAuthOptions,
+    issue: &LicenseIssuer,
+    verify: &LicenseVerifier,
+    reporter: &dyn AndroidAuthReporter,
+    device: &AdbDevice,
+) -> DeviceAuthOutcome {
+    let serial = &device.serial;
+    reporter.log("info", format!("开始处理设备: {}", device.label()));
+    let mut outcome = DeviceAuthOutcome {
+        serial: serial.clone(),
+        label: device.label(),
+        remote_dir: None,
+        device_code: None,
+        error: None,
+        verification_error: None,
+    };
+    for remote_dir in &options.remote_dirs {
+        reporter.log("info", format!("设备 {}, 尝试路径: {}", serial, remote_dir));
+        match authorize_at(backend, options, issue, reporter, serial, remote_dir) {
+            Ok((device_code, license_bytes)) => {
+                outcome.remote_dir = Some(remote_dir.clone());
+                outcome.device_code = Some(device_code.to_string());
+                outcome.verification_error = verify_pushed(
+                    backend,
+                    verify,
+                    reporter,
+                    serial,
+                    &remote_join(remote_dir, &options.license_file),
+                    &device_code,
+                    &license_bytes,
+                )
+                .err();
+                let level = if outcome.succeeded() { "info" } else { "error" };
+                reporter.log(level, outcome.summary());
+                return outcome;
+            }
+            Err(e) => reporter.log(
+                "warn",
+                format!("设备 {} 在路径 {} 授权失败: {}.", serial, remote_dir, e),
+            ),
+        }
+    }
+    let err_msg = "在所有已知路径均授权失败".to_string();
+    reporter.log("error", format!("设备 {} {}。", serial, err_msg));
+    outcome.error = Some(err_msg);
+    outcome
+}
+
+fn authorize_at(
+    backend: &dyn AdbBackend,
+    options: &AndroidAuthOptions,
+    issue: &LicenseIssuer,
+    reporter: &dyn AndroidAuthReporter,
+    serial: &str,
+    remote_dir: &str,
+) -> Result<(DeviceCode, Vec<u8>), String> {
+    let device_code_file = options.device_code_file.as_str();
+    let device_code_path = remote_join(remote_dir, device_code_file);
+    let content = backend
+        .pull(serial, &device_code_path, &mut |progress| {
+            reporter.transfer(serial, device_code_file, progress)
+        })
+        .map_err(|e| format!("设备码拉取失败 ({}): {}", serial, e))?;
+    let device_code = DeviceCode::parse(&String::from_utf8_lossy(&content))
+        .map_err(|e| format!("设备 {} 的设备码无效: {}", serial, e))?;
+    reporter.log(
+        "info",
+        format!("准备为设备 {} (设备码: {}) 授权", serial, device_code),
+    );
+
+    let license_bytes = issue(&device_code)?;
+    let license_path = adb_backend::push_file(
+        backend,
+        serial,
+        &license_bytes,
+        remote_dir,
+        &options.license_file,
+        options.push_mode,
+        &mut |progress| reporter.transfer(serial, &options.license_file, progress),
+    )
+    .map_err(|e| format!("授权文件推送失败 ({}): {}", serial, e))?;
+    reporter.log(
+        "info",
+        format!("授权文件已推送到设备 {} 的 {}", serial, license_path),
+    );
+    Ok((device_code, license_bytes))
+}
+
+// 推送后回读授权文件：内容与签发时一致，并且对设备上的设备码校验通过才算授权成功
+fn verify_pushed(
+    backend: &dyn AdbBackend,
+    verify: &LicenseVerifier,
+    reporter: &dyn AndroidAuthReporter,
+    serial: &str,
+    license_path: &str,
+    device_code: &DeviceCode,
+    license_bytes: &[u8],
+) -> Result<(), String> {
+    let pushed = backend
+        .pull(serial, license_path, &mut |_| {})
+        .map_err(|e| format!("回读授权文件失败: {}", e))?;
+    let expected = hex::encode(Sha256::digest(license_bytes));
+    let actual = hex::encode(Sha256::digest(&pushed));
+    if actual != expected {
+        return Err(format!(
+            "设备上的授权文件与签发内容不一致 (SHA-256 {}，应为 {})",
+            actual, expected
+        ));
+    }
+    verify(&pushed, device_code).map_err(|e| format!("授权文件校验失败: {}", e))?;
+    reporter.log("info", format!("设备 {} 上的授权文件回读校验通过", serial));
+    Ok(())
+}
+
+// 未授权、离线等设备不会被处理，逐个提示操作员如何处理
+pub fn log_unready_devices(reporter: &dyn AndroidAuthReporter, devices: &[AdbDevice]) {
+    for device in devices.iter().filter(|device| !device.is_ready()) {
+        reporter.log(
+            "warn",
+            format!(
+                "设备 {} 状态为 {}: {}",
+                device.label(),
+                device.state_text,
+                device.guidance.as_deref().unwrap_or("")
+            ),
+        );
+    }
+}
+
+pub(crate) fn remote_join(dir: &str, file_name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), file_name)
+}
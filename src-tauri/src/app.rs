@@ -0,0 +1,1443 @@
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager, RunEvent, State, Window, Wry};
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::{env, fs};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::adb_client::{
+    parse_network_address, validate_pairing_code, AdbDevice, MdnsService, TransferProgress,
+};
+use crate::adb_utils::{self, AdbServerBackend};
+use crate::android_auth::{
+    self, AndroidAuthOptions, AndroidAuthReporter, DeviceAuthOutcome, DEFAULT_MAX_CONCURRENCY,
+    DEVICE_CODE_FILE_NAME, LICENSE_FILE_NAME,
+};
+use crate::app_profile::{AppProfile, AppProfiles};
+use crate::device_code::DeviceCode;
+use crate::device_watcher::{AutoAuthorizePool, DeviceTracker};
+use crate::en_de_crypt::encrypt;
+use crate::entitlements::{self, Edition, Entitlements, FeatureSet};
+use crate::fingerprint::{DeviceFingerprint, FingerprintMatch, DEFAULT_MATCH_THRESHOLD_PERCENT};
+use crate::get_device_code;
+use crate::kdf::{self, KdfAlgorithm};
+use crate::keystore::{self, IssuingKey, KeystoreStatus, UnlockedKeystore};
+use crate::license_data::AuthorizationData;
+use crate::license_format::LicenseContainer;
+use crate::license_mac::compute_mac;
+use crate::license_request::LicenseRequest;
+use crate::license_sign::sign_payload;
+use crate::verifier_keys::VerifierKeySet;
+use crate::verify::{
+    self, VerifiedLicense, VerifyOptions, DEFAULT_CLOCK_SKEW_SECS, DEFAULT_PRODUCT_ID,
+};
+use crate::verify_error::{VerifyError, VerifyErrorKind};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+// 全局静态变量，用于标记是否正在退出
+static IS_EXITING: AtomicBool = AtomicBool::new(false);
+
+const WATCHER_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(3); // 设备监听断开后的重连间隔
+
+// 签发时由前端选择的授权条款
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LicenseTerms {
+    pub not_before: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub edition: Edition,
+    #[serde(default)]
+    pub features: FeatureSet,
+    // 授权文件绑定的产品标识，为空时使用 DEFAULT_PRODUCT_ID
+    #[serde(default)]
+    pub product_id: Option<String>,
+    // 硬件指纹匹配阈值 (百分比)，为空时使用 DEFAULT_MATCH_THRESHOLD_PERCENT
+    #[serde(default)]
+    pub fingerprint_threshold_percent: Option<u8>,
+}
+
+impl LicenseTerms {
+    fn product_id(&self) -> &str {
+        self.product_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .unwrap_or(DEFAULT_PRODUCT_ID)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        entitlements::validate_features(&self.features)?;
+        if let Some(threshold) = self.fingerprint_threshold_percent {
+            if !(1..=100).contains(&threshold) {
+                return Err(format!(
+                    "硬件指纹匹配阈值须在 1 到 100 之间 (当前: {})",
+                    threshold
+                ));
+            }
+        }
+        if let (Some(not_before), Some(expires_at)) = (self.not_before, self.expires_at) {
+            if expires_at <= not_before {
+                return Err(format!(
+                    "到期时间 ({}) 必须晚于生效时间 ({})",
+                    expires_at, not_before
+                ));
+            }
+        }
+        if let Some(expires_at) = self.expires_at {
+            if expires_at <= Utc::now() {
+                return Err(format!("到期时间 ({}) 早于当前时间", expires_at));
+            }
+        }
+        Ok(())
+    }
+}
+
+// New struct to return combined authorization and verification result
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WindowsAuthResult {
+    pub authorization_message: String,
+    pub verification_status: String,
+    pub verification_details: Option<AuthorizationData>, // None if verification fails
+    pub verification_error: Option<VerifyError>,         // None if verification succeeds
+    pub entitlements: Option<Entitlements>,              // 校验通过时的版本与功能授权
+    pub fingerprint_match: Option<FingerprintMatch>,     // 硬件指纹比对结果，含已变化的组件
+}
+
+// 按文本设备码签发的结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssuedLicense {
+    pub license_path: String,
+    pub device_code: String, // 带校验位的分组显示形式
+    pub auth_data: AuthorizationData,
+    pub verification_error: Option<VerifyError>, // 签发后自检失败时的原因
+}
+
+// Wi-Fi 设备列表：已连接的网络设备与 mDNS 发现的无线调试服务
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkDevices {
+    pub devices: Vec<AdbDevice>,
+    pub services: Vec<MdnsService>,
+}
+
+pub struct AppState {
+    adb_path: Mutex<String>,
+    key_dir: PathBuf,                          // 密钥库所在目录 (应用配置目录)
+    keystore: Mutex<Option<UnlockedKeystore>>, // 解锁后才能签发授权
+    device_watcher: Mutex<Option<DeviceWatcherHandle>>, // 设备热插拔监听，未开启时为 None
+}
+
+// 后台设备监听线程；stop 置位后线程在下一次轮询时退出
+struct DeviceWatcherHandle {
+    stop: Arc<AtomicBool>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl DeviceWatcherHandle {
+    fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
+
+// 自动授权模式下签发所需的密钥与条款，在开启监听时确定
+struct AutoAuthorize {
+    issuing_key: IssuingKey,
+    verifier_keys: VerifierKeySet,
+    terms: LicenseTerms,
+    options: AndroidAuthOptions, // 所选应用配置的目录与文件名，以及同时授权的设备数上限
+}
+
+impl AppState {
+    fn new(app_handle: &AppHandle<Wry>) -> Self {
+        let adb_executable_name = if cfg!(windows) { "adb.exe" } else { "adb" };
+        let adb_resource_path = app_handle
+            .path()
+            .resolve(
+                format!("platform-tools/{}", adb_executable_name),
+                BaseDirectory::Resource,
+            )
+            .expect("Failed to resolve ADB resource path.");
+        let key_dir = app_handle
+            .path()
+            .app_config_dir()
+            .expect("Failed to resolve app config dir.");
+        Self {
+            adb_path: Mutex::new(adb_resource_path.to_string_lossy().into_owned()),
+            key_dir,
+            keystore: Mutex::new(None),
+            device_watcher: Mutex::new(None),
+        }
+    }
+
+    // 取得当前签发密钥，密钥库未解锁时返回错误
+    fn issuing_key(&self) -> Result<IssuingKey, String> {
+        self.issuing_key_for(None)
+    }
+
+    // key_id 为空时取当前密钥
+    fn issuing_key_for(&self, key_id: Option<&str>) -> Result<IssuingKey, String> {
+        let keystore = self.keystore.lock().unwrap();
+        let keystore = keystore
+            .as_ref()
+            .ok_or_else(|| "密钥库未解锁，请先在密钥管理中输入口令".to_string())?;
+        match key_id {
+            Some(key_id) => keystore.issuing_key(key_id),
+            None => keystore.active_key(),
+        }
+    }
+
+    // 每次使用时重新读取，修改配置文件后无需重启
+    fn app_profiles(&self) -> Result<AppProfiles, String> {
+        AppProfiles::load(&self.key_dir)
+    }
+}
+
+fn log_to_frontend<S: Into<String> + serde::Serialize>(
+    window: &Window<Wry>,
+    level: &str,
+    message: S,
+) {
+    let log_entry = format!("[{}] {}", level.to_uppercase(), message.into());
+    println!("{}", log_entry);
+    if let Err(e) = window.emit("log_message", log_entry) {
+        eprintln!("Failed to emit log event: {}", e);
+    }
+}
+
+fn new_authorization_data(
+    device_code: &DeviceCode,
+    terms: &LicenseTerms,
+    fingerprint: Option<DeviceFingerprint>,
+    request_nonce: Option<&str>,
+    issuing_key: &IssuingKey,
+) -> Result<AuthorizationData, String> {
+    let fingerprint = fingerprint.map(|mut fingerprint| {
+        if let Some(threshold) = terms.fingerprint_threshold_percent {
+            fingerprint.threshold_percent = threshold;
+        }
+        fingerprint
+    });
+    let mut auth_data = AuthorizationData {
+        device_code: device_code.as_str().to_string(),
+        issued_at: Utc::now(),
+        not_before: terms.not_before,
+        expires_at: terms.expires_at,
+        edition: terms.edition,
+        features: terms.features.clone(),
+        fingerprint,
+        request_nonce: request_nonce.map(str::to_string),
+        product_id: Some(terms.product_id().to_string()),
+        serial_number: Uuid::new_v4().to_string(),
+        checksum: String::new(),
+    };
+    auth_data.checksum = compute_mac(&auth_data, &kdf::derive_mac_key(&issuing_key.license_key)?);
+    Ok(auth_data)
+}
+
+// 签名并加密授权数据，生成授权文件内容；设备码与产品标识作为附加数据参与认证
+fn seal_license(
+    auth_data: &AuthorizationData,
+    issuing_key: &IssuingKey,
+    product_id: &str,
+) -> Result<Vec<u8>, String> {
+    let json_data = serde_json::to_string(auth_data).map_err(|e| e.to_string())?;
+    let signed_license = sign_payload(&json_data, &issuing_key.key_id, &issuing_key.signing_key);
+    let signed_json = serde_json::to_string(&signed_license).map_err(|e| e.to_string())?;
+    let salt = kdf::random_salt()?;
+    let encryption_key = kdf::derive_license_encryption_key(
+        KdfAlgorithm::HkdfSha256,
+        &issuing_key.license_key,
+        &salt,
+    )?;
+    let mut container = LicenseContainer::new(&issuing_key.key_id, salt.to_vec());
+    let aad = container.associated_data(&auth_data.device_code, product_id);
+    let (encrypted_data, nonce) =
+        encrypt(signed_json.as_bytes(), &encryption_key, &aad).map_err(|e| e.to_string())?;
+    container.nonce = nonce;
+    container.payload = encrypted_data;
+    container.to_bytes()
+}
+
+#[tauri::command]
+async fn get_keystore_status(app_state: State<'_, AppState>) -> Result<KeystoreStatus, String> {
+    match app_state.keystore.lock().unwrap().as_ref() {
+        Some(keystore) => Ok(keystore.status()),
+        None => Ok(keystore::locked_status(&app_state.key_dir)),
+    }
+}
+
+#[tauri::command]
+async fn unlock_keystore(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<KeystoreStatus, String> {
+    log_to_frontend(
+        &window,
+        "info",
+        format!("解锁密钥库: {}", app_state.key_dir.display()),
+    );
+    let key_dir = app_state.key_dir.clone();
+    // Argon2id 派生较耗时，放到阻塞线程中执行
+    let unlocked = tauri::async_runtime::spawn_blocking(move || {
+        UnlockedKeystore::open_or_create(&key_dir, &passphrase)
+    })
+    .await
+    .map_err(|e| format!("unlock_keystore spawn_blocking error: {}", e))?
+    .inspect_err(|e| {
+        log_to_frontend(&window, "error", format!("解锁密钥库失败: {}", e));
+    })?;
+
+    let status = unlocked.status();
+    *app_state.keystore.lock().unwrap() = Some(unlocked);
+    log_to_frontend(
+        &window,
+        "info",
+        format!("密钥库已解锁，当前签名密钥: {:?}", status.active_key_id),
+    );
+    Ok(status)
+}
+
+#[tauri::command]
+async fn lock_keystore(app_state: State<'_, AppState>) -> Result<KeystoreStatus, String> {
+    *app_state.keystore.lock().unwrap() = None;
+    Ok(keystore::locked_status(&app_state.key_dir))
+}
+
+#[tauri::command]
+async fn rotate_signing_key(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+) -> Result<KeystoreStatus, String> {
+    let mut guard = app_state.keystore.lock().unwrap();
+    let keystore = guard
+        .as_mut()
+        .ok_or_else(|| "密钥库未解锁，无法轮换密钥".to_string())?;
+    let key_id = keystore.rotate().inspect_err(|e| {
+        log_to_frontend(&window, "error", format!("轮换签名密钥失败: {}", e));
+    })?;
+    log_to_frontend(
+        &window,
+        "info",
+        format!("已生成新的签名密钥: {}，旧密钥保留用于校验", key_id),
+    );
+    Ok(keystore.status())
+}
+
+#[tauri::command]
+async fn list_adb_devices(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<AdbDevice>, String> {
+    log_to_frontend(&window, "info", "请求刷新 ADB 设备列表...");
+    let adb_p = app_state.adb_path.lock().unwrap().clone();
+    match adb_utils::adb_devices_cmd(&adb_p).await {
+        Ok(devices) => {
+            let labels: Vec<String> = devices.iter().map(AdbDevice::label).collect();
+            log_to_frontend(&window, "info", format!("发现设备: {:?}", labels));
+            android_auth::log_unready_devices(&WindowReporter { window }, &devices);
+            Ok(devices)
+        }
+        Err(e) => {
+            let err_msg = format!("获取设备列表失败: {}", e);
+            log_to_frontend(&window, "error", err_msg.clone());
+            Err(err_msg)
+        }
+    }
+}
+
+// 无线调试配对 (Android 11+)：头显 "无线调试 -> 使用配对码配对设备" 中显示的地址与配对码
+#[tauri::command]
+async fn pair_adb_device(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    address: String,
+    pairing_code: String,
+) -> Result<String, String> {
+    let address = parse_network_address(&address, None)
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    let pairing_code = validate_pairing_code(&pairing_code)
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    log_to_frontend(&window, "info", format!("正在与 {} 配对...", address));
+    let adb_p = app_state.adb_path.lock().unwrap().clone();
+    let message = adb_utils::adb_pair_cmd(&adb_p, &address, pairing_code)
+        .await
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    log_to_frontend(
+        &window,
+        "info",
+        format!(
+            "{}。请使用头显无线调试页面显示的 IP 地址和端口连接",
+            message
+        ),
+    );
+    Ok(message)
+}
+
+// adb connect；省略端口时使用 5555
+#[tauri::command]
+async fn connect_adb_device(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    address: String,
+) -> Result<String, String> {
+    let address = parse_network_address(&address, Some(adb_utils::DEFAULT_WIRELESS_PORT))
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    log_to_frontend(&window, "info", format!("正在连接 {}...", address));
+    let adb_p = app_state.adb_path.lock().unwrap().clone();
+    let message = adb_utils::adb_connect_cmd(&adb_p, &address)
+        .await
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    log_to_frontend(&window, "info", message.clone());
+    Ok(message)
+}
+
+#[tauri::command]
+async fn disconnect_adb_device(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    address: String,
+) -> Result<String, String> {
+    let address = parse_network_address(&address, Some(adb_utils::DEFAULT_WIRELESS_PORT))
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    let adb_p = app_state.adb_path.lock().unwrap().clone();
+    let message = adb_utils::adb_disconnect_cmd(&adb_p, &address)
+        .await
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    log_to_frontend(&window, "info", message.clone());
+    Ok(message)
+}
+
+#[tauri::command]
+async fn list_network_devices(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+) -> Result<NetworkDevices, String> {
+    let adb_p = app_state.adb_path.lock().unwrap().clone();
+    let (devices, services) = adb_utils::adb_network_devices_cmd(&adb_p)
+        .await
+        .inspect_err(|e| log_to_frontend(&window, "error", e.clone()))?;
+    Ok(NetworkDevices { devices, services })
+}
+
+// ADB 传输进度事件，前端据此更新进度条
+#[derive(Serialize, Clone)]
+struct AdbTransferEvent {
+    device_id: String,
+    file_name: String,
+    #[serde(flatten)]
+    progress: TransferProgress,
+}
+
+// 把 Android 授权流程的日志与传输进度转发到前端
+struct WindowReporter {
+    window: Window<Wry>,
+}
+
+impl AndroidAuthReporter for WindowReporter {
+    fn log(&self, level: &str, message: String) {
+        log_to_frontend(&self.window, level, message);
+    }
+
+    fn transfer(&self, serial: &str, file_name: &str, progress: TransferProgress) {
+        let event = AdbTransferEvent {
+            device_id: serial.to_string(),
+            file_name: file_name.to_string(),
+            progress,
+        };
+        if let Err(e) = self.window.emit("adb_transfer_progress", event) {
+            eprintln!("Failed to emit transfer progress: {}", e);
+        }
+    }
+}
+
+// Renamed from generate_auth_file_cmd to reflect its new role and parameters
+// This function will now be called internally by authorize_windows_application
+async fn generate_auth_file_for_app(
+    window: &Window<Wry>, // Passed as reference
+    app_state: &AppState,
+    device_code: &DeviceCode,
+    target_app_path: &Path, // Use Path directly
+    terms: &LicenseTerms,
+    fingerprint: Option<DeviceFingerprint>,
+) -> Result<PathBuf, String> {
+    // Returns path to the generated .lic file
+    log_to_frontend(
+        window,
+        "info",
+        format!(
+            "为设备 {} 在应用路径 {} 生成授权文件",
+            device_code,
+            target_app_path.display()
+        ),
+    );
+
+    let issuing_key = app_state.issuing_key().inspect_err(|e| {
+        log_to_frontend(window, "error", e.clone());
+    })?;
+
+    if !target_app_path.is_dir() {
+        let err_msg = format!(
+            "提供的应用路径不是一个有效的目录: {}",
+            target_app_path.display()
+        );
+        log_to_frontend(window, "error", err_msg.clone());
+        return Err(err_msg);
+    }
+
+    let auth_data = new_authorization_data(device_code, terms, fingerprint, None, &issuing_key)
+        .inspect_err(|e| {
+            log_to_frontend(window, "error", format!("生成授权数据失败: {}", e));
+        })?;
+
+    let license_bytes =
+        seal_license(&auth_data, &issuing_key, terms.product_id()).inspect_err(|e| {
+            log_to_frontend(window, "error", format!("生成授权内容失败: {}", e));
+        })?;
+
+    let auth_file_full_path = target_app_path.join(LICENSE_FILE_NAME);
+    fs::write(&auth_file_full_path, license_bytes).map_err(|e| {
+        let err_msg = format!(
+            "写入授权文件失败 ({}): {}",
+            auth_file_full_path.display(),
+            e
+        );
+        log_to_frontend(window, "error", err_msg.clone());
+        err_msg
+    })?;
+
+    Ok(auth_file_full_path)
+}
+
+// Modified to take app_path and derive file paths from it
+// This will be called internally by authorize_windows_application
+async fn check_authorization_for_app(
+    window: &Window<Wry>, // Passed as reference
+    app_state: &AppState,
+    app_path: &Path,
+    product_id: &str,
+    clock_skew: Duration,
+    live_fingerprint: Option<DeviceFingerprint>,
+) -> Result<VerifiedLicense, VerifyError> {
+    let auth_file_path = app_path.join(LICENSE_FILE_NAME);
+    let device_code_file_path = app_path.join(DEVICE_CODE_FILE_NAME);
+
+    log_to_frontend(
+        window,
+        "info",
+        format!(
+            "请求验证应用路径 {} 下的授权 (授权文件: {}, 设备码文件: {}, 产品: {})",
+            app_path.display(),
+            auth_file_path.display(),
+            device_code_file_path.display(),
+            product_id
+        ),
+    );
+
+    verify_license_files(
+        window,
+        app_state,
+        &auth_file_path,
+        &device_code_file_path,
+        product_id,
+        clock_skew,
+        live_fingerprint,
+    )
+    .inspect_err(|e| {
+        log_to_frontend(window, "error", format!("[{:?}] {}", e.kind, e.message));
+    })
+}
+
+fn verify_license_files(
+    window: &Window<Wry>,
+    app_state: &AppState,
+    auth_file_path: &Path,
+    device_code_file_path: &Path,
+    product_id: &str,
+    clock_skew: Duration,
+    live_fingerprint: Option<DeviceFingerprint>,
+) -> Result<VerifiedLicense, VerifyError> {
+    let verifier_keys = keystore::load_verifier_keys(&app_state.key_dir)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::KeyUnavailable, e))?;
+
+    let device_code_from_file = fs::read_to_string(device_code_file_path).map_err(|e| {
+        VerifyError::new(
+            VerifyErrorKind::MissingFile,
+            format!(
+                "读取设备码文件 {} 失败: {}",
+                device_code_file_path.display(),
+                e
+            ),
+        )
+    })?;
+
+    let license_bytes = fs::read(auth_file_path).map_err(|e| {
+        VerifyError::new(
+            VerifyErrorKind::MissingFile,
+            format!("读取授权文件 {} 失败: {}", auth_file_path.display(), e),
+        )
+    })?;
+
+    let options = VerifyOptions {
+        product_id: product_id.to_string(),
+        clock_skew,
+        now: Utc::now(),
+        live_fingerprint,
+        skip_fingerprint: false,
+        allow_legacy: false,
+    };
+    let verified = verify::verify_license(
+        &license_bytes,
+        &device_code_from_file,
+        &verifier_keys,
+        &options,
+    )?;
+    log_to_frontend(
+        window,
+        "info",
+        format!(
+            "授权文件格式版本: {}, 密钥 ID: {}",
+            verified.format_version, verified.key_id
+        ),
+    );
+    if let Some(fingerprint_match) = &verified.fingerprint_match {
+        if !fingerprint_match.drifted.is_empty() {
+            log_to_frontend(
+                window,
+                "warn",
+                format!(
+                    "硬件指纹部分变化 (匹配 {}/{}，要求 {}%)，已变化的组件: {}",
+                    fingerprint_match.matched_weight,
+                    fingerprint_match.total_weight,
+                    fingerprint_match.threshold_percent,
+                    fingerprint_match.drifted.join(", ")
+                ),
+            );
+        }
+    }
+
+    Ok(verified)
+}
+
+// New command for the improved Windows authorization flow
+#[tauri::command]
+async fn authorize_windows_application(
+    window: Window<Wry>,
+    _app_handle: AppHandle<Wry>, // Added AppHandle
+    app_state: State<'_, AppState>,
+    application_path_str: String,
+    license_terms: Option<LicenseTerms>,
+    clock_skew_secs: Option<i64>,
+) -> Result<WindowsAuthResult, String> {
+    log_to_frontend(
+        &window,
+        "info",
+        format!(
+            "开始 Windows 应用授权流程，目标路径: {}",
+            application_path_str
+        ),
+    );
+
+    let license_terms = license_terms.unwrap_or_default();
+    license_terms.validate().inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+    let clock_skew =
+        verify::clock_skew_from_secs(clock_skew_secs.unwrap_or(DEFAULT_CLOCK_SKEW_SECS))
+            .inspect_err(|e| {
+                log_to_frontend(&window, "error", e.clone());
+            })?;
+
+    let application_path = PathBuf::from(&application_path_str);
+    if !application_path.is_dir() {
+        let err_msg = format!(
+            "提供的应用路径不是一个有效的目录: {}",
+            application_path.display()
+        );
+        log_to_frontend(&window, "error", err_msg.clone());
+        return Err(err_msg);
+    }
+    // Ensure the path is allowed by FS scope if it's not a subdirectory of a base directory
+    // This might be needed if you use restricted FS scopes. For simplicity, we assume broad access or correct setup.
+    // You might need to add `application_path_str` to `fs.scope` in `tauri.conf.json` or use `dialog.open`
+    // which automatically grants temporary access. Since the user selects it, temporary access is granted.
+
+    // 本机硬件指纹：设备码文件缺失时用于生成设备码，设备码一致时写入授权做容错比对
+    let local_device = tauri::async_runtime::spawn_blocking(get_device_code::local_device)
+        .await
+        .map_err(|e| format!("读取本机硬件信息任务失败: {}", e))
+        .and_then(|result| result);
+
+    // 1. get device code from file; 文件不存在时在本机生成设备码
+    let device_code = match fs::read_to_string(application_path.join(DEVICE_CODE_FILE_NAME)) {
+        Ok(code) => {
+            let device_code = DeviceCode::parse(&code).map_err(|e| {
+                let err_msg = format!("设备码文件内容无效: {}", e);
+                log_to_frontend(&window, "error", err_msg.clone());
+                err_msg
+            })?;
+            log_to_frontend(&window, "info", format!("读取到设备码: {}", device_code));
+            device_code
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            log_to_frontend(
+                &window,
+                "info",
+                "未找到设备码文件，根据本机硬件信息生成设备码",
+            );
+            let generated = local_device
+                .as_ref()
+                .map_err(|e| e.clone())
+                .and_then(|device| DeviceCode::parse(&device.device_code))
+                .inspect_err(|e| {
+                    log_to_frontend(&window, "error", e.clone());
+                })?;
+            log_to_frontend(&window, "info", format!("生成设备码: {}", generated));
+            generated
+        }
+        Err(e) => {
+            let err_msg = format!("读取设备码文件失败: {}", e);
+            log_to_frontend(&window, "error", err_msg.clone());
+            return Err(err_msg);
+        }
+    };
+
+    let fingerprint = match &local_device {
+        Ok(device) if device.device_code == device_code.as_str() => {
+            let names: Vec<_> = device
+                .fingerprint
+                .components
+                .iter()
+                .map(|c| format!("{}(权重 {})", c.name, c.weight))
+                .collect();
+            log_to_frontend(
+                &window,
+                "info",
+                format!("授权将记录本机硬件指纹: {}", names.join(", ")),
+            );
+            Some(device.fingerprint.clone())
+        }
+        Ok(_) => {
+            log_to_frontend(&window, "info", "设备码不是本机生成的，授权不记录硬件指纹");
+            None
+        }
+        Err(e) => {
+            log_to_frontend(
+                &window,
+                "warn",
+                format!("读取本机硬件信息失败，授权不记录硬件指纹: {}", e),
+            );
+            None
+        }
+    };
+
+    // 2. Save device_code.bin to application_path (规范形式，不带校验位)
+    let device_code_file_path = application_path.join(DEVICE_CODE_FILE_NAME);
+    if let Err(e) = fs::write(&device_code_file_path, device_code.as_str()) {
+        let err_msg = format!(
+            "写入设备码文件 {} 失败: {}",
+            device_code_file_path.display(),
+            e
+        );
+        log_to_frontend(&window, "error", err_msg.clone());
+        return Err(err_msg);
+    }
+    log_to_frontend(
+        &window,
+        "info",
+        format!("设备码文件已保存到: {}", device_code_file_path.display()),
+    );
+
+    // 3. Generate license.lic in application_path
+    let auth_file_full_path = match generate_auth_file_for_app(
+        &window,
+        &app_state,
+        &device_code,
+        &application_path,
+        &license_terms,
+        fingerprint,
+    )
+    .await
+    {
+        Ok(path) => {
+            log_to_frontend(
+                &window,
+                "info",
+                format!("授权文件已生成在: {}", path.display()),
+            );
+            path
+        }
+        Err(e) => {
+            log_to_frontend(&window, "error", format!("生成授权文件失败: {}", e));
+            return Err(format!("生成授权文件失败: {}", e));
+        }
+    };
+
+    let authorization_message = format!("授权文件已成功生成在 {}", auth_file_full_path.display());
+
+    // 4. Automatically verify
+    log_to_frontend(&window, "info", "开始自动校验生成的授权...");
+    match check_authorization_for_app(
+        &window,
+        &app_state,
+        &application_path,
+        license_terms.product_id(),
+        clock_skew,
+        local_device.ok().map(|device| device.fingerprint),
+    )
+    .await
+    {
+        Ok(verified) => {
+            let auth_data = verified.auth_data;
+            let success_msg = format!(
+                "自动校验通过! 设备码: {}, 序列号: {}, 时间: {}, 版本: {:?}, 功能: {:?}",
+                auth_data.device_code,
+                auth_data.serial_number,
+                auth_data.issued_at,
+                auth_data.edition,
+                auth_data.features
+            );
+            log_to_frontend(&window, "info", success_msg.clone());
+            Ok(WindowsAuthResult {
+                authorization_message,
+                verification_status: "校验通过".to_string(),
+                entitlements: Some(auth_data.entitlements()),
+                verification_details: Some(auth_data),
+                verification_error: None,
+                fingerprint_match: verified.fingerprint_match,
+            })
+        }
+        Err(e) => {
+            log_to_frontend(&window, "error", format!("自动校验失败: {}", e));
+            Ok(WindowsAuthResult {
+                authorization_message, // Still report auth success
+                verification_status: format!("校验失败: {}", e),
+                verification_details: None,
+                verification_error: Some(e),
+                entitlements: None,
+                fingerprint_match: None,
+            })
+        }
+    }
+}
+
+// 按客户通过聊天或邮件发来的设备码签发授权，不需要设备码文件
+#[tauri::command]
+async fn issue_license_for_device_code(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    device_code: String,
+    output_path: String,
+    license_terms: Option<LicenseTerms>,
+) -> Result<IssuedLicense, String> {
+    let license_terms = license_terms.unwrap_or_default();
+    license_terms.validate().inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+
+    let device_code = DeviceCode::parse_entered(&device_code).inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+
+    let license_path = resolve_license_output_path(&output_path).inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+    log_to_frontend(
+        &window,
+        "info",
+        format!(
+            "为设备码 {} 签发授权，输出到 {}",
+            device_code,
+            license_path.display()
+        ),
+    );
+    issue_license_to_path(
+        &window,
+        &app_state,
+        &device_code,
+        &license_path,
+        &license_terms,
+        None,
+        None,
+    )
+}
+
+// 按客户端生成的授权申请文件签发：使用申请中的设备码与硬件摘要，并回写申请编号
+#[tauri::command]
+async fn issue_license_from_request(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    request_path: String,
+    output_path: String,
+    license_terms: Option<LicenseTerms>,
+) -> Result<IssuedLicense, String> {
+    let mut license_terms = license_terms.unwrap_or_default();
+    license_terms.validate().inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+    let request = read_license_request_file(&request_path).inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+
+    // 未指定产品时按申请中的产品签发；指定了则必须与申请一致
+    let specified_product = license_terms
+        .product_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+    match specified_product {
+        None => license_terms.product_id = Some(request.product_id.clone()),
+        Some(product_id) if product_id != request.product_id => {
+            let err_msg = format!(
+                "授权申请的产品 ({}) 与签发设置中的产品 ({}) 不一致",
+                request.product_id, product_id
+            );
+            log_to_frontend(&window, "error", err_msg.clone());
+            return Err(err_msg);
+        }
+        Some(_) => {}
+    }
+
+    let license_path = resolve_license_output_path(&output_path).inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+    log_to_frontend(
+        &window,
+        "info",
+        format!(
+            "按授权申请 {} 为设备码 {} (客户端版本 {}{}) 签发授权，输出到 {}",
+            request.nonce,
+            request.device_code,
+            request.client_version,
+            request
+                .customer_reference
+                .as_deref()
+                .map(|reference| format!("，客户备注: {}", reference))
+                .unwrap_or_default(),
+            license_path.display()
+        ),
+    );
+    log_to_frontend(
+        &window,
+        "warn",
+        "授权申请文件只带完整性校验和，不能证明申请来源，请确认申请文件来自可信渠道",
+    );
+    let fingerprint = (!request.hardware.is_empty()).then(|| DeviceFingerprint {
+        threshold_percent: DEFAULT_MATCH_THRESHOLD_PERCENT,
+        components: request.hardware.clone(),
+    });
+    issue_license_to_path(
+        &window,
+        &app_state,
+        &request.device_code,
+        &license_path,
+        &license_terms,
+        fingerprint,
+        Some(&request.nonce),
+    )
+}
+
+// 读取授权申请文件供界面预览，签发前由操作员核对
+#[tauri::command]
+async fn read_license_request(request_path: String) -> Result<LicenseRequest, String> {
+    read_license_request_file(&request_path)
+}
+
+fn read_license_request_file(request_path: &str) -> Result<LicenseRequest, String> {
+    let request_path = Path::new(request_path.trim());
+    let bytes = fs::read(request_path)
+        .map_err(|e| format!("读取授权申请文件 {} 失败: {}", request_path.display(), e))?;
+    LicenseRequest::from_file_bytes(&bytes)
+}
+
+// 输出位置为目录时写入其中的 license.lic，否则视为授权文件路径
+fn resolve_license_output_path(output_path: &str) -> Result<PathBuf, String> {
+    let output_path = PathBuf::from(output_path.trim());
+    let license_path = if output_path.is_dir() {
+        output_path.join(LICENSE_FILE_NAME)
+    } else {
+        output_path
+    };
+    if !license_path.parent().is_some_and(|dir| dir.is_dir()) {
+        return Err(format!("输出目录不存在: {}", license_path.display()));
+    }
+    Ok(license_path)
+}
+
+// 签发并写入授权文件，随后用本机校验密钥自检
+fn issue_license_to_path(
+    window: &Window<Wry>,
+    app_state: &AppState,
+    device_code: &DeviceCode,
+    license_path: &Path,
+    license_terms: &LicenseTerms,
+    fingerprint: Option<DeviceFingerprint>,
+    request_nonce: Option<&str>,
+) -> Result<IssuedLicense, String> {
+    let issuing_key = app_state.issuing_key().inspect_err(|e| {
+        log_to_frontend(window, "error", e.clone());
+    })?;
+    let auth_data = new_authorization_data(
+        device_code,
+        license_terms,
+        fingerprint,
+        request_nonce,
+        &issuing_key,
+    )
+    .inspect_err(|e| {
+        log_to_frontend(window, "error", format!("生成授权数据失败: {}", e));
+    })?;
+    let license_bytes = seal_license(&auth_data, &issuing_key, license_terms.product_id())
+        .inspect_err(|e| {
+            log_to_frontend(window, "error", format!("生成授权内容失败: {}", e));
+        })?;
+    fs::write(license_path, &license_bytes).map_err(|e| {
+        let err_msg = format!("写入授权文件 {} 失败: {}", license_path.display(), e);
+        log_to_frontend(window, "error", err_msg.clone());
+        err_msg
+    })?;
+    log_to_frontend(
+        window,
+        "info",
+        format!("授权文件已生成在: {}", license_path.display()),
+    );
+
+    // 客户端不在本机，只能用设备码对刚生成的内容做一次自检 (无法比对硬件指纹)；
+    // 延后生效的授权按生效时间检查
+    let options = VerifyOptions {
+        product_id: license_terms.product_id().to_string(),
+        clock_skew: Duration::seconds(DEFAULT_CLOCK_SKEW_SECS),
+        now: auth_data.not_before.unwrap_or_else(Utc::now),
+        live_fingerprint: None,
+        skip_fingerprint: true,
+        allow_legacy: false,
+    };
+    let verification_error = keystore::load_verifier_keys(&app_state.key_dir)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::KeyUnavailable, e))
+        .and_then(|keys| {
+            verify::verify_license(&license_bytes, device_code.as_str(), &keys, &options)
+        })
+        .inspect_err(|e| {
+            log_to_frontend(window, "error", format!("授权自检失败: {}", e));
+        })
+        .err();
+
+    Ok(IssuedLicense {
+        license_path: license_path.display().to_string(),
+        device_code: device_code.to_string(),
+        auth_data,
+        verification_error,
+    })
+}
+
+#[tauri::command]
+async fn process_android_authorization(
+    window: Window<Wry>,
+    batch_mode: bool, // 重新接收 batch_mode
+    license_terms: Option<LicenseTerms>,
+    max_concurrency: Option<usize>, // 批量模式同时处理的设备数，为空时使用默认值
+    profile_id: Option<String>,     // 应用配置 ID，为空时使用默认配置
+    app_state: State<'_, AppState>,
+) -> Result<String, String> {
+    log_to_frontend(
+        &window,
+        "info",
+        format!("开始处理 Android 授权, 批量模式: {}", batch_mode),
+    );
+
+    let license_terms = license_terms.unwrap_or_default();
+    license_terms.validate().inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+    let profile = select_app_profile(&window, &app_state, profile_id.as_deref())?;
+    let issuing_key = app_state
+        .issuing_key_for(profile.key_id.as_deref())
+        .inspect_err(|e| {
+            log_to_frontend(&window, "error", e.clone());
+        })?;
+
+    // 推送后回读校验所用的公钥，与客户端内置的一致
+    let verifier_keys = keystore::load_verifier_keys(&app_state.key_dir).inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })?;
+
+    let backend = AdbServerBackend::new(app_state.adb_path.lock().unwrap().clone());
+    let options = AndroidAuthOptions {
+        batch_mode,
+        max_concurrency: max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY),
+        ..profile.auth_options()
+    };
+    let reporter = WindowReporter { window };
+    let outcomes = tauri::async_runtime::spawn_blocking(move || {
+        let issue = |device_code: &DeviceCode| {
+            issue_android_license(device_code, &license_terms, &issuing_key)
+        };
+        let verify = |license_bytes: &[u8], device_code: &DeviceCode| {
+            verify_android_license(license_bytes, device_code, &license_terms, &verifier_keys)
+        };
+        android_auth::authorize_android_devices(&backend, &options, &issue, &verify, &reporter)
+    })
+    .await
+    .map_err(|e| format!("Android 授权任务异常结束: {}", e))??;
+
+    if outcomes.is_empty() {
+        Ok("未处理任何设备或所有设备均处理失败。".to_string())
+    } else {
+        Ok(outcomes
+            .iter()
+            .map(DeviceAuthOutcome::summary)
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+// 应用配置列表，前端据此选择本次授权的客户端应用
+#[tauri::command]
+async fn list_app_profiles(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+) -> Result<AppProfiles, String> {
+    app_state.app_profiles().inspect_err(|e| {
+        log_to_frontend(&window, "error", e.clone());
+    })
+}
+
+fn select_app_profile(
+    window: &Window<Wry>,
+    app_state: &AppState,
+    profile_id: Option<&str>,
+) -> Result<AppProfile, String> {
+    let profile = app_state
+        .app_profiles()
+        .and_then(|profiles| profiles.get(profile_id).cloned())
+        .inspect_err(|e| {
+            log_to_frontend(window, "error", e.clone());
+        })?;
+    log_to_frontend(
+        window,
+        "info",
+        format!(
+            "使用应用配置: {} ({}){}",
+            profile.name,
+            profile.package_name,
+            profile
+                .key_id
+                .as_deref()
+                .map(|key_id| format!("，签发密钥: {}", key_id))
+                .unwrap_or_default()
+        ),
+    );
+    Ok(profile)
+}
+
+// Android 授权不记录硬件指纹，设备码由头显上的客户端生成
+fn issue_android_license(
+    device_code: &DeviceCode,
+    terms: &LicenseTerms,
+    issuing_key: &IssuingKey,
+) -> Result<Vec<u8>, String> {
+    let auth_data = new_authorization_data(device_code, terms, None, None, issuing_key)?;
+    seal_license(&auth_data, issuing_key, terms.product_id())
+}
+
+// 与客户端相同的完整校验；延后生效的授权按生效时间检查
+fn verify_android_license(
+    license_bytes: &[u8],
+    device_code: &DeviceCode,
+    terms: &LicenseTerms,
+    keys: &VerifierKeySet,
+) -> Result<(), String> {
+    let options = VerifyOptions {
+        product_id: terms.product_id().to_string(),
+        clock_skew: Duration::seconds(DEFAULT_CLOCK_SKEW_SECS),
+        now: terms.not_before.unwrap_or_else(Utc::now),
+        live_fingerprint: None,
+        skip_fingerprint: false,
+        allow_legacy: false,
+    };
+    verify::verify_license(license_bytes, device_code.as_str(), keys, &options)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// 开启设备热插拔监听；auto_authorize 为 true 时对每台新连接的可用设备自动授权一次。
+// 已在监听时按新的设置重新开启
+#[tauri::command]
+async fn start_device_watcher(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+    auto_authorize: bool,
+    license_terms: Option<LicenseTerms>,
+    profile_id: Option<String>,
+    max_concurrency: Option<usize>, // 与批量模式共用的并发上限，为空时使用默认值
+) -> Result<(), String> {
+    let auto = if auto_authorize {
+        let terms = license_terms.unwrap_or_default();
+        terms.validate().inspect_err(|e| {
+            log_to_frontend(&window, "error", e.clone());
+        })?;
+        let profile = select_app_profile(&window, &app_state, profile_id.as_deref())?;
+        let issuing_key = app_state
+            .issuing_key_for(profile.key_id.as_deref())
+            .inspect_err(|e| {
+                log_to_frontend(&window, "error", e.clone());
+            })?;
+        let verifier_keys = keystore::load_verifier_keys(&app_state.key_dir).inspect_err(|e| {
+            log_to_frontend(&window, "error", e.clone());
+        })?;
+        Some(Arc::new(AutoAuthorize {
+            issuing_key,
+            verifier_keys,
+            terms,
+            options: AndroidAuthOptions {
+                max_concurrency: max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY),
+                ..profile.auth_options()
+            },
+        }))
+    } else {
+        None
+    };
+
+    let previous = app_state.device_watcher.lock().unwrap().take();
+    if let Some(previous) = previous {
+        tauri::async_runtime::spawn_blocking(move || previous.stop())
+            .await
+            .map_err(|e| format!("停止设备监听失败: {}", e))?;
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let adb_path = app_state.adb_path.lock().unwrap().clone();
+    let thread = {
+        let stop = stop.clone();
+        let window = window.clone();
+        thread::spawn(move || run_device_watcher(window, adb_path, stop, auto))
+    };
+    *app_state.device_watcher.lock().unwrap() = Some(DeviceWatcherHandle { stop, thread });
+    log_to_frontend(
+        &window,
+        "info",
+        if auto_authorize {
+            "已开启设备监听，新连接的设备将自动授权"
+        } else {
+            "已开启设备监听"
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_device_watcher(
+    window: Window<Wry>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    let watcher = app_state.device_watcher.lock().unwrap().take();
+    if let Some(watcher) = watcher {
+        tauri::async_runtime::spawn_blocking(move || watcher.stop())
+            .await
+            .map_err(|e| format!("停止设备监听失败: {}", e))?;
+        log_to_frontend(&window, "info", "已停止设备监听");
+    }
+    Ok(())
+}
+
+// 监听线程主循环：ADB 服务器断开时等待后重连，直到 stop 置位
+fn run_device_watcher(
+    window: Window<Wry>,
+    adb_path: String,
+    stop: Arc<AtomicBool>,
+    auto: Option<Arc<AutoAuthorize>>,
+) {
+    let mut tracker = DeviceTracker::new();
+    let pool = auto.map(|auto| {
+        let window = window.clone();
+        let adb_path = adb_path.clone();
+        AutoAuthorizePool::new(auto.options.max_concurrency, move |device| {
+            auto_authorize_device(&window, &adb_path, &auto, device)
+        })
+    });
+    while !stop.load(Ordering::Relaxed) {
+        let result = adb_utils::connect_client(&adb_path).and_then(|client| {
+            client.track_devices(&stop, &mut |devices| {
+                let events = tracker.update(devices);
+                for event in &events {
+                    if let Err(e) = window.emit("adb_device_event", event) {
+                        eprintln!("Failed to emit device event: {}", e);
+                    }
+                }
+                if let Some(pool) = &pool {
+                    for device in tracker.take_auto_authorize(&events) {
+                        pool.submit(device);
+                    }
+                }
+            })
+        });
+        if let Err(e) = result {
+            log_to_frontend(
+                &window,
+                "warn",
+                format!(
+                    "设备监听中断: {}，{} 秒后重试",
+                    e,
+                    WATCHER_RETRY_DELAY.as_secs()
+                ),
+            );
+            // 已断开的设备在重连后的第一份列表中不会出现，届时补发断开事件
+            let deadline = Instant::now() + WATCHER_RETRY_DELAY;
+            while Instant::now() < deadline && !stop.load(Ordering::Relaxed) {
+                thread::sleep(std::time::Duration::from_millis(200));
+            }
+        }
+    }
+    // 等待已排队的设备处理完再返回，停止监听后关闭 ADB 服务时不会有工作线程再拉起它
+    if let Some(pool) = pool {
+        pool.finish();
+    }
+}
+
+fn auto_authorize_device(
+    window: &Window<Wry>,
+    adb_path: &str,
+    auto: &AutoAuthorize,
+    device: AdbDevice,
+) {
+    let reporter = WindowReporter {
+        window: window.clone(),
+    };
+    let backend = AdbServerBackend::new(adb_path.to_string());
+    reporter.log("info", format!("检测到新设备 {}，自动授权", device.label()));
+    let issue = |device_code: &DeviceCode| {
+        issue_android_license(device_code, &auto.terms, &auto.issuing_key)
+    };
+    let verify = |license_bytes: &[u8], device_code: &DeviceCode| {
+        verify_android_license(license_bytes, device_code, &auto.terms, &auto.verifier_keys)
+    };
+    let outcome = android_auth::authorize_device(
+        &backend,
+        &auto.options,
+        &issue,
+        &verify,
+        &reporter,
+        &device,
+    );
+    if let Err(e) = reporter.window.emit("adb_auto_authorized", &outcome) {
+        eprintln!("Failed to emit auto authorization result: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn get_executable_dir(_app_handle: AppHandle) -> Result<String, String> {
+    match env::current_exe() {
+        Ok(exe_path) => {
+            if let Some(exe_dir) = exe_path.parent() {
+                Ok(exe_dir.to_string_lossy().into_owned())
+            } else {
+                Err("无法获取可执行文件的父目录".to_string())
+            }
+        }
+        Err(e) => Err(format!("无法获取可执行文件路径: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn kill_adb_server_on_exit(app_handle: AppHandle<Wry>) -> Result<String, String> {
+    println!("[INFO] 应用退出前：尝试关闭 ADB 服务..."); // 使用 println! 因为此时窗口可能已关闭
+
+    // 先停止设备监听 (含自动授权线程)，否则监听线程重连时会再次拉起 ADB 服务
+    let app_state: State<'_, AppState> = app_handle.state();
+    let watcher = app_state.device_watcher.lock().unwrap().take();
+    if let Some(watcher) = watcher {
+        let _ = tauri::async_runtime::spawn_blocking(move || watcher.stop()).await;
+    }
+    match adb_utils::adb_kill_cmd().await {
+        Ok(msg) => {
+            println!("[INFO] ADB 服务关闭成功: {}", msg);
+            Ok(msg)
+        }
+        Err(e) => {
+            eprintln!("[ERROR] 关闭 ADB 服务失败: {}", e);
+            Err(e)
+        }
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    let builder = tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_process::init())
+        .setup(|app| {
+            app.manage(AppState::new(app.handle()));
+
+            #[cfg(target_os = "windows")]
+            {
+                use window_vibrancy::apply_acrylic;
+                let window = app.get_webview_window("main").unwrap(); // 获取主窗口的引用
+                if let Err(e) = apply_acrylic(&window, None) {
+                    eprintln!("Failed to apply Mica effect: {}", e);
+                }
+            }
+
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            get_keystore_status,
+            unlock_keystore,
+            lock_keystore,
+            rotate_signing_key,
+            list_adb_devices,
+            list_app_profiles,
+            pair_adb_device,
+            connect_adb_device,
+            disconnect_adb_device,
+            list_network_devices,
+            authorize_windows_application,
+            issue_license_for_device_code,
+            issue_license_from_request,
+            read_license_request,
+            process_android_authorization,
+            start_device_watcher,
+            stop_device_watcher,
+            get_executable_dir,
+            kill_adb_server_on_exit
+        ]);
+
+    builder
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| match event {
+            // 尝试获取退出锁，如果成功（之前是false，现在设置为true），则执行清理
+            RunEvent::ExitRequested { api, .. }
+                if IS_EXITING
+                    .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok() =>
+            {
+                println!("[INFO] 应用退出请求 (ExitRequested)，首次进入，准备关闭 ADB 服务...");
+                let app_handle_clone = app_handle.clone();
+                api.prevent_exit(); // 阻止立即退出
+
+                tauri::async_runtime::spawn(async move {
+                    println!("[INFO] 在异步任务中执行 kill_adb_server_on_exit...");
+                    match kill_adb_server_on_exit(app_handle_clone.clone()).await {
+                        Ok(_) => {
+                            println!("[INFO] ADB 服务已成功关闭 (来自 ExitRequested 事件)。")
+                        }
+                        Err(e) => eprintln!("[ERROR] 应用退出时关闭 ADB 服务失败: {}", e),
+                    }
+
+                    println!("[INFO] ADB 清理完成，现在请求应用退出。");
+                    app_handle_clone.exit(0);
+                });
+            }
+            _ => {}
+        });
+}
@@ -0,0 +1,188 @@
+// 应用配置：每个头显客户端应用的包名、设备码/授权文件所在目录与文件名、签发密钥。
+// 从配置目录下的 app_profiles.json 读取，文件不存在时使用内置的 ALVR 客户端配置
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::android_auth::{
+    AndroidAuthOptions, DEFAULT_ANDROID_CLIENT_DIRS, DEVICE_CODE_FILE_NAME, LICENSE_FILE_NAME,
+};
+
+pub const APP_PROFILES_FILE_NAME: &str = "app_profiles.json";
+pub const DEFAULT_APP_PROFILE_ID: &str = "alvr";
+
+const PACKAGE_PLACEHOLDER: &str = "{package}";
+
+fn default_device_code_file() -> String {
+    DEVICE_CODE_FILE_NAME.to_string()
+}
+
+fn default_license_file() -> String {
+    LICENSE_FILE_NAME.to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AppProfile {
+    pub id: String,
+    pub name: String,
+    pub package_name: String,
+    // 按顺序尝试的客户端目录，可用 {package} 代替包名
+    pub remote_dirs: Vec<String>,
+    #[serde(default = "default_device_code_file")]
+    pub device_code_file: String,
+    #[serde(default = "default_license_file")]
+    pub license_file: String,
+    // 签发使用的密钥 ID，为空时使用密钥库的当前密钥
+    #[serde(default)]
+    pub key_id: Option<String>,
+}
+
+impl AppProfile {
+    fn builtin() -> Self {
+        Self {
+            id: DEFAULT_APP_PROFILE_ID.to_string(),
+            name: "ALVR 客户端 (BUPT-VR)".to_string(),
+            package_name: "alvr.client.stable".to_string(),
+            remote_dirs: DEFAULT_ANDROID_CLIENT_DIRS
+                .iter()
+                .map(|dir| dir.to_string())
+                .collect(),
+            device_code_file: default_device_code_file(),
+            license_file: default_license_file(),
+            key_id: None,
+        }
+    }
+
+    // 展开 {package} 后的客户端目录
+    pub fn remote_dirs(&self) -> Vec<String> {
+        self.remote_dirs
+            .iter()
+            .map(|dir| dir.replace(PACKAGE_PLACEHOLDER, &self.package_name))
+            .collect()
+    }
+
+    // 按本配置处理设备的授权参数，批量模式与并发数由调用方设置
+    pub fn auth_options(&self) -> AndroidAuthOptions {
+        AndroidAuthOptions {
+            remote_dirs: self.remote_dirs(),
+            device_code_file: self.device_code_file.clone(),
+            license_file: self.license_file.clone(),
+            ..AndroidAuthOptions::default()
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let field = |name: &str, value: &str| {
+            if value.trim().is_empty() {
+                Err(format!("应用配置 \"{}\" 的 {} 不能为空", self.id, name))
+            } else {
+                Ok(())
+            }
+        };
+        field("id", &self.id)?;
+        field("name", &self.name)?;
+        field("package_name", &self.package_name)?;
+        let package_ok = self.package_name.contains('.')
+            && self
+                .package_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+        if !package_ok {
+            return Err(format!(
+                "应用配置 \"{}\" 的包名无效: {}",
+                self.id, self.package_name
+            ));
+        }
+        if self.remote_dirs.is_empty() {
+            return Err(format!("应用配置 \"{}\" 没有客户端目录", self.id));
+        }
+        if let Some(dir) = self.remote_dirs.iter().find(|dir| !dir.starts_with('/')) {
+            return Err(format!(
+                "应用配置 \"{}\" 的客户端目录必须是绝对路径: {}",
+                self.id, dir
+            ));
+        }
+        for file_name in [&self.device_code_file, &self.license_file] {
+            if file_name.is_empty()
+                || file_name == "."
+                || file_name == ".."
+                || file_name.contains('/')
+            {
+                return Err(format!(
+                    "应用配置 \"{}\" 的文件名无效: \"{}\"",
+                    self.id, file_name
+                ));
+            }
+        }
+        if self.device_code_file == self.license_file {
+            return Err(format!(
+                "应用配置 \"{}\" 的设备码文件与授权文件不能同名",
+                self.id
+            ));
+        }
+        if let Some(key_id) = &self.key_id {
+            field("key_id", key_id)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AppProfiles {
+    pub default_profile: String,
+    pub profiles: Vec<AppProfile>,
+}
+
+impl Default for AppProfiles {
+    fn default() -> Self {
+        Self {
+            default_profile: DEFAULT_APP_PROFILE_ID.to_string(),
+            profiles: vec![AppProfile::builtin()],
+        }
+    }
+}
+
+impl AppProfiles {
+    // 读取 config_dir 下的 app_profiles.json，文件不存在时返回内置配置
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        let path = config_dir.join(APP_PROFILES_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("读取应用配置 {} 失败: {}", path.display(), e))?;
+        Self::from_json(&json).map_err(|e| format!("应用配置 {} 无效: {}", path.display(), e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let profiles: Self =
+            serde_json::from_str(json).map_err(|e| format!("解析应用配置失败: {}", e))?;
+        profiles.validate()?;
+        Ok(profiles)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.profiles.is_empty() {
+            return Err("应用配置中没有任何应用".to_string());
+        }
+        for (i, profile) in self.profiles.iter().enumerate() {
+            profile.validate()?;
+            if self.profiles[..i].iter().any(|p| p.id == profile.id) {
+                return Err(format!("应用配置 ID 重复: {}", profile.id));
+            }
+        }
+        self.get(None).map(|_| ())
+    }
+
+    // 按 ID 选择配置，为空时使用默认配置
+    pub fn get(&self, id: Option<&str>) -> Result<&AppProfile, String> {
+        let id = id
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .unwrap_or(&self.default_profile);
+        self.profiles
+            .iter()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| format!("未找到应用配置: {}", id))
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CODE_LEN: usize = 16;
+
+// 设备码：16 位十六进制。内部统一保存为小写 (与设备端 device_code.bin 及已签发授权一致)，
+// 给人读写时显示为带校验位的分组格式 XXXX-XXXX-XXXX-XXXX-C，校验位为 Luhn mod 16
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct DeviceCode(String);
+
+impl DeviceCode {
+    // 解析设备码：忽略大小写、空白和分隔符，允许不带校验位 (设备端生成的文件)
+    pub fn parse(input: &str) -> Result<Self, String> {
+        Self::parse_with(input, false)
+    }
+
+    // 解析人工录入的设备码：必须带校验位，防止读错或抄错
+    pub fn parse_entered(input: &str) -> Result<Self, String> {
+        Self::parse_with(input, true)
+    }
+
+    fn parse_with(input: &str, require_check: bool) -> Result<Self, String> {
+        let normalized = normalize(input);
+        if normalized.is_empty() {
+            return Err("设备码为空".to_string());
+        }
+        if let Some(c) = normalized.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(format!("设备码 \"{}\" 含有无效字符 '{}'", input.trim(), c));
+        }
+        let (code, check) = match normalized.len() {
+            CODE_LEN if !require_check => (&normalized[..], None),
+            CODE_LEN => {
+                return Err(format!(
+                    "设备码 \"{}\" 缺少校验位，请按 XXXX-XXXX-XXXX-XXXX-C 格式完整输入 (旧版客户端请改用授权申请文件)",
+                    input.trim()
+                ))
+            }
+            len if len == CODE_LEN + 1 => (&normalized[..CODE_LEN], Some(&normalized[CODE_LEN..])),
+            len => {
+                return Err(format!(
+                    "设备码 \"{}\" 长度错误 ({} 位，应为 {} 位十六进制加 1 位校验位)",
+                    input.trim(),
+                    len,
+                    CODE_LEN
+                ))
+            }
+        };
+        let code = code.to_ascii_lowercase();
+        if let Some(check) = check {
+            let expected = check_char(&code);
+            if !check.eq_ignore_ascii_case(&expected.to_string()) {
+                return Err(format!(
+                    "设备码 \"{}\" 校验位错误，请核对每一位是否抄写正确",
+                    input.trim()
+                ));
+            }
+        }
+        Ok(Self(code))
+    }
+
+    // 规范形式：16 位小写十六进制，用于签名、附加数据和比较
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// 显示为带校验位的分组形式，供人读写
+impl fmt::Display for DeviceCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let upper = self.0.to_ascii_uppercase();
+        for i in (0..CODE_LEN).step_by(4) {
+            write!(f, "{}-", &upper[i..i + 4])?;
+        }
+        write!(f, "{}", check_char(&self.0))
+    }
+}
+
+impl TryFrom<String> for DeviceCode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<DeviceCode> for String {
+    fn from(code: DeviceCode) -> Self {
+        code.0
+    }
+}
+
+// 去掉空白和分隔符并转为大写；电话报读时常见的 O/I/L 视为 0/1
+fn normalize(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '_' | '.'))
+        .map(|c| match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            other => other,
+        })
+        .collect()
+}
+
+// Luhn mod 16：能发现任意单个字符错误和绝大多数相邻字符调换
+fn check_char(code: &str) -> char {
+    let mut sum = 0;
+    for (i, c) in code.chars().rev().enumerate() {
+        let value = c.to_digit(16).unwrap_or(0);
+        let addend = if i % 2 == 0 { value * 2 } else { value };
+        sum += addend / 16 + addend % 16;
+    }
+    let check = (16 - sum % 16) % 16;
+    char::from_digit(check, 16)
+        .unwrap_or('0')
+        .to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "fb95dce2cc77799c";
+    const HEX: &str = "0123456789abcdef";
+
+    fn entered(code: &str) -> String {
+        format!("{}{}", code, check_char(code))
+    }
+
+    #[test]
+    fn detects_every_single_character_error() {
+        let check = check_char(CODE);
+        for i in 0..CODE_LEN {
+            for c in HEX.chars().filter(|c| *c != CODE.as_bytes()[i] as char) {
+                let mut wrong = CODE.to_string();
+                wrong.replace_range(i..i + 1, &c.to_string());
+                let input = format!("{}{}", wrong, check);
+                assert!(DeviceCode::parse_entered(&input).is_err(), "{}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn detects_adjacent_transpositions_except_0_and_f() {
+        // Luhn mod 16 唯一发现不了的相邻调换是 0 与 F
+        for a in HEX.chars() {
+            for b in HEX.chars().filter(|b| *b != a) {
+                let code = format!("{}{}{}", &CODE[..8], a, b) + &CODE[10..];
+                let swapped = format!("{}{}{}", &CODE[..8], b, a) + &CODE[10..];
+                let detected = check_char(&code) != check_char(&swapped);
+                let undetectable = matches!((a, b), ('0', 'f') | ('f', '0'));
+                assert_eq!(detected, !undetectable, "{} <-> {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn displays_grouped_upper_case_with_check() {
+        let code = DeviceCode::parse(CODE).unwrap();
+        let display = code.to_string();
+        assert_eq!(display, format!("FB95-DCE2-CC77-799C-{}", check_char(CODE)));
+        assert_eq!(code.as_str(), CODE);
+
+        // 分组、大小写和空白不影响解析结果
+        for input in [
+            display.clone(),
+            display.to_lowercase(),
+            display.replace('-', ""),
+            display.replace('-', " "),
+            format!("  {}\n", display.replace('-', "_")),
+        ] {
+            assert_eq!(
+                DeviceCode::parse_entered(&input),
+                Ok(code.clone()),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn maps_o_i_l_to_digits() {
+        let code = DeviceCode::parse_entered(&entered(HEX)).unwrap();
+        let misread = format!("OI23-4567-89AB-CDEF-{}", check_char(HEX));
+        assert_eq!(DeviceCode::parse_entered(&misread), Ok(code.clone()));
+        let misread = format!("o l23 4567 89ab cdef {}", check_char(HEX));
+        assert_eq!(DeviceCode::parse_entered(&misread), Ok(code));
+    }
+
+    #[test]
+    fn reports_length_and_missing_check_errors() {
+        assert!(DeviceCode::parse("").unwrap_err().contains("为空"));
+        assert!(DeviceCode::parse("fb95dce2cc77799")
+            .unwrap_err()
+            .contains("长度错误"));
+        assert!(DeviceCode::parse(&format!("{}00", CODE))
+            .unwrap_err()
+            .contains("长度错误"));
+        assert!(DeviceCode::parse("fb95dce2cc77799g")
+            .unwrap_err()
+            .contains("无效字符"));
+
+        // 设备端文件可以不带校验位，人工录入必须带
+        assert!(DeviceCode::parse(CODE).is_ok());
+        assert!(DeviceCode::parse_entered(CODE)
+            .unwrap_err()
+            .contains("缺少校验位"));
+        assert!(DeviceCode::parse(&entered(CODE)).is_ok());
+
+        let wrong_check = format!(
+            "{}{}",
+            CODE,
+            if check_char(CODE) == '0' { '1' } else { '0' }
+        );
+        assert!(DeviceCode::parse(&wrong_check)
+            .unwrap_err()
+            .contains("校验位错误"));
+    }
+}
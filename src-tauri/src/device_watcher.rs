@@ -0,0 +1,132 @@
+// 设备热插拔跟踪：比较 track-devices 相邻两次的设备列表，得出连接/断开事件，
+// 并记录自动授权模式下已处理过的设备，保证每台设备只自动授权一次
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::adb_client::AdbDevice;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeviceEvent {
+    Connected { device: AdbDevice },
+    StateChanged { device: AdbDevice }, // 例如在头显上允许调试后由 unauthorized 变为 device
+    Disconnected { serial: String },
+}
+
+#[derive(Debug, Default)]
+pub struct DeviceTracker {
+    known: HashMap<String, AdbDevice>,
+    auto_authorized: HashSet<String>, // 本次监听中已自动授权过的序列号，重新插拔也不再处理
+}
+
+impl DeviceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 用最新的完整设备列表更新状态，返回与上一次相比的变化
+    pub fn update(&mut self, devices: Vec<AdbDevice>) -> Vec<DeviceEvent> {
+        let mut events = Vec::new();
+        let current: HashSet<&str> = devices.iter().map(|d| d.serial.as_str()).collect();
+        let mut gone: Vec<String> = self
+            .known
+            .keys()
+            .filter(|serial| !current.contains(serial.as_str()))
+            .cloned()
+            .collect();
+        gone.sort();
+        for serial in gone {
+            self.known.remove(&serial);
+            events.push(DeviceEvent::Disconnected { serial });
+        }
+        for device in devices {
+            match self.known.get(&device.serial) {
+                None => events.push(DeviceEvent::Connected {
+                    device: device.clone(),
+                }),
+                Some(previous) if previous.state != device.state => {
+                    events.push(DeviceEvent::StateChanged {
+                        device: device.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+            self.known.insert(device.serial.clone(), device);
+        }
+        events
+    }
+
+    // 从本次的变化中挑出需要自动授权的设备：已就绪且本次监听中尚未处理过
+    pub fn take_auto_authorize(&mut self, events: &[DeviceEvent]) -> Vec<AdbDevice> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                DeviceEvent::Connected { device } | DeviceEvent::StateChanged { device } => {
+                    Some(device)
+                }
+                DeviceEvent::Disconnected { .. } => None,
+            })
+            .filter(|device| device.is_ready())
+            .filter(|device| self.auto_authorized.insert(device.serial.clone()))
+            .cloned()
+            .collect()
+    }
+
+    pub fn devices(&self) -> Vec<AdbDevice> {
+        let mut devices: Vec<AdbDevice> = self.known.values().cloned().collect();
+        devices.sort_by(|a, b| a.serial.cmp(&b.serial));
+        devices
+    }
+}
+
+// 自动授权的工作线程：最多 workers 台设备同时处理，其余排队，
+// 与批量模式使用同一个并发上限，避免一次插入大量设备时线程和 USB 带宽失控
+pub struct AutoAuthorizePool {
+    sender: Option<Sender<AdbDevice>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl AutoAuthorizePool {
+    // workers 为 0 时视为 1
+    pub fn new(workers: usize, authorize: impl Fn(AdbDevice) + Send + Sync + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel::<AdbDevice>();
+        let receiver: Arc<Mutex<Receiver<AdbDevice>>> = Arc::new(Mutex::new(receiver));
+        let authorize = Arc::new(authorize);
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                let authorize = authorize.clone();
+                thread::spawn(move || loop {
+                    // 只在取任务时持有锁，处理期间其他线程可以继续取
+                    let device = receiver.lock().unwrap().recv();
+                    match device {
+                        Ok(device) => authorize(device),
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn submit(&self, device: AdbDevice) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(device);
+        }
+    }
+
+    // 不再接收新设备，等待已排队的设备处理完毕。
+    // 直接丢弃时工作线程同样会处理完队列后退出，只是不等待
+    pub fn finish(mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
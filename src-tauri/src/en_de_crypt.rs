@@ -1,57 +1,108 @@
 use aes_gcm::{
     Aes256Gcm, Nonce,
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
 };
-use base64::{Engine as _, engine::general_purpose::STANDARD};
+#[cfg(any(test, feature = "app"))]
 use rand::{TryRngCore, rngs::OsRng};
-use std::str;
+use std::fmt;
 
-// 加密函数
-pub(crate) fn encrypt(data: &str, key: &[u8]) -> (String, String) {
-    // 确保密钥长度正确（32字节）
-    let key = if key.len() < 32 {
-        let mut new_key = [0u8; 32];
-        new_key[..key.len()].copy_from_slice(key);
-        new_key
-    } else {
-        key[0..32].try_into().expect("密钥长度错误")
-    };
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
 
-    let cipher = Aes256Gcm::new((&key).into());
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    // 解密结果不是有效的 UTF-8 等编码问题
+    BadEncoding(String),
+    // 密钥、nonce 等定长字段长度不正确
+    WrongLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    // AES-GCM 认证失败：密钥错误或密文被篡改，算法本身无法区分两者
+    AuthenticationFailed,
+    RandomUnavailable(String),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::BadEncoding(e) => write!(f, "数据编码无效: {}", e),
+            CryptoError::WrongLength {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} 长度错误 (应为 {} 字节, 实际 {} 字节)",
+                field, expected, actual
+            ),
+            CryptoError::AuthenticationFailed => write!(f, "解密失败: 密钥错误或数据已被篡改"),
+            CryptoError::RandomUnavailable(e) => write!(f, "无法生成随机数: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+// 加密函数，返回 (密文, nonce)；key 须为经 kdf 派生的 32 字节密钥，aad 为不加密但参与认证的附加数据
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn encrypt(
+    data: &[u8],
+    key: &[u8],
+    aad: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+    let cipher = new_cipher(key)?;
 
     // 正确生成随机 nonce (12 字节)
-    let mut nonce_bytes = [0u8; 12];
+    let mut nonce_bytes = [0u8; NONCE_LEN];
     OsRng
         .try_fill_bytes(nonce_bytes.as_mut_slice())
-        .expect("无法生成随机数");
+        .map_err(|e| CryptoError::RandomUnavailable(e.to_string()))?;
     let nonce = Nonce::from_slice(&nonce_bytes);
 
-    let ciphertext = cipher.encrypt(nonce, data.as_bytes()).expect("加密失败");
+    let ciphertext = cipher
+        .encrypt(nonce, Payload { msg: data, aad })
+        .map_err(|_| CryptoError::AuthenticationFailed)?;
 
-    (STANDARD.encode(&ciphertext), STANDARD.encode(nonce))
+    Ok((ciphertext, nonce_bytes.to_vec()))
 }
 
 // 解密函数
-pub(crate) fn decrypt(encrypted_data: &str, nonce: &str, key: &[u8]) -> String {
-    // 确保密钥长度正确（32字节）
-    let key = if key.len() < 32 {
-        let mut new_key = [0u8; 32];
-        new_key[..key.len()].copy_from_slice(key);
-        new_key
-    } else {
-        key[0..32].try_into().expect("密钥长度错误")
-    };
-
-    let cipher = Aes256Gcm::new((&key).into());
-
-    // 修复：先将解码结果存储在变量中
-    let decoded_nonce = STANDARD.decode(nonce).expect("无效的nonce");
-    let nonce = Nonce::from_slice(&decoded_nonce);
-
-    let decoded_data = STANDARD.decode(encrypted_data).expect("无效的密文");
-    let decrypted_ciphertext = cipher.decrypt(nonce, &*decoded_data).expect("解密失败");
-
-    str::from_utf8(&decrypted_ciphertext)
-        .expect("无效的UTF-8数据")
-        .to_string()
+pub(crate) fn decrypt(
+    encrypted_data: &[u8],
+    nonce: &[u8],
+    key: &[u8],
+    aad: &[u8],
+) -> Result<String, CryptoError> {
+    if nonce.len() != NONCE_LEN {
+        return Err(CryptoError::WrongLength {
+            field: "nonce",
+            expected: NONCE_LEN,
+            actual: nonce.len(),
+        });
+    }
+    let cipher = new_cipher(key)?;
+    let nonce = Nonce::from_slice(nonce);
+
+    let decrypted_ciphertext = cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: encrypted_data,
+                aad,
+            },
+        )
+        .map_err(|_| CryptoError::AuthenticationFailed)?;
+
+    String::from_utf8(decrypted_ciphertext).map_err(|e| CryptoError::BadEncoding(e.to_string()))
+}
+
+// 密钥长度必须正好为 32 字节，不再对短密钥补零或截断长密钥
+fn new_cipher(key: &[u8]) -> Result<Aes256Gcm, CryptoError> {
+    Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::WrongLength {
+        field: "key",
+        expected: KEY_LEN,
+        actual: key.len(),
+    })
 }
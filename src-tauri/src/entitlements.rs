@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// 产品版本；旧版授权文件没有该字段，按标准版处理
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Edition {
+    Trial,
+    #[default]
+    Standard,
+    Professional,
+    Enterprise,
+}
+
+// 功能项的取值：开关 (如 multiplayer) 或数量上限 (如 max_sessions)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FeatureValue {
+    Flag(bool),
+    Limit(u64),
+}
+
+// 使用 BTreeMap 保证序列化顺序固定
+pub type FeatureSet = BTreeMap<String, FeatureValue>;
+
+// 校验通过后返回给客户端、用于功能开关的授权内容
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Entitlements {
+    pub edition: Edition,
+    pub features: FeatureSet,
+}
+
+impl Entitlements {
+    // 开关类功能是否开启；数量类功能大于 0 视为开启
+    pub fn has_feature(&self, name: &str) -> bool {
+        match self.features.get(name) {
+            Some(FeatureValue::Flag(enabled)) => *enabled,
+            Some(FeatureValue::Limit(limit)) => *limit > 0,
+            None => false,
+        }
+    }
+
+    pub fn feature_limit(&self, name: &str) -> Option<u64> {
+        match self.features.get(name) {
+            Some(FeatureValue::Limit(limit)) => Some(*limit),
+            _ => None,
+        }
+    }
+}
+
+// 功能名只允许小写字母、数字和下划线，避免签发时的拼写差异
+#[cfg(feature = "app")]
+pub(crate) fn validate_features(features: &FeatureSet) -> Result<(), String> {
+    for name in features.keys() {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            return Err(format!(
+                "功能名 \"{}\" 无效 (只允许小写字母、数字和下划线)",
+                name
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,916 @@
+// 供 Unity/C++ 客户端调用的 C 接口，头文件由 build.rs 生成到 include/authorization.h
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::device_code::DeviceCode;
+use crate::fingerprint::FingerprintMatch;
+use crate::license_data::AuthorizationData;
+use crate::license_request::LicenseRequest;
+use crate::verifier_keys::VerifierKeySet;
+use crate::verify::{self, VerifyOptions};
+use crate::verify_error::{VerifyError, VerifyErrorKind};
+
+/// C 接口版本，接口有不兼容修改时递增
+pub const AUTH_ABI_VERSION: u32 = 2;
+
+/// 接口返回码：0 为成功，1..=99 与校验错误类别一一对应，100 起为调用错误
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthStatus {
+    Ok = 0,
+    MissingFile = 1,
+    KeyUnavailable = 2,
+    BadEncoding = 3,
+    WrongLength = 4,
+    WrongKey = 5,
+    Tampered = 6,
+    DeviceMismatch = 7,
+    BindingMismatch = 8,
+    NotYetValid = 9,
+    Expired = 10,
+    FingerprintMismatch = 11,
+    InvalidDeviceCode = 12,
+    ReissueRequired = 13,
+    InvalidArgument = 100,
+    Internal = 101,
+}
+
+impl From<VerifyErrorKind> for AuthStatus {
+    fn from(kind: VerifyErrorKind) -> Self {
+        match kind {
+            VerifyErrorKind::MissingFile => Self::MissingFile,
+            VerifyErrorKind::KeyUnavailable => Self::KeyUnavailable,
+            VerifyErrorKind::BadEncoding => Self::BadEncoding,
+            VerifyErrorKind::WrongLength => Self::WrongLength,
+            VerifyErrorKind::WrongKey => Self::WrongKey,
+            VerifyErrorKind::Tampered => Self::Tampered,
+            VerifyErrorKind::DeviceMismatch => Self::DeviceMismatch,
+            VerifyErrorKind::BindingMismatch => Self::BindingMismatch,
+            VerifyErrorKind::NotYetValid => Self::NotYetValid,
+            VerifyErrorKind::Expired => Self::Expired,
+            VerifyErrorKind::FingerprintMismatch => Self::FingerprintMismatch,
+            VerifyErrorKind::InvalidDeviceCode => Self::InvalidDeviceCode,
+            VerifyErrorKind::ReissueRequired => Self::ReissueRequired,
+        }
+    }
+}
+
+/// 校验通过的授权 (不透明句柄)，用 auth_license_free 释放
+pub struct AuthLicense {
+    data: AuthorizationData,
+    fingerprint_match: Option<FingerprintMatch>, // 授权没有记录硬件指纹时为 None
+}
+
+thread_local! {
+    // 当前线程最近一次失败的错误信息
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+struct FfiError(AuthStatus, String);
+
+impl From<VerifyError> for FfiError {
+    fn from(e: VerifyError) -> Self {
+        FfiError(e.kind.into(), e.message)
+    }
+}
+
+fn invalid_argument(message: impl Into<String>) -> FfiError {
+    FfiError(AuthStatus::InvalidArgument, message.into())
+}
+
+// 执行调用并把错误写入 LAST_ERROR；panic 不会跨越 C 边界
+fn run_guarded(f: impl FnOnce() -> Result<(), FfiError>) -> AuthStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            AuthStatus::Ok
+        }
+        Ok(Err(FfiError(status, message))) => {
+            set_last_error(message);
+            status
+        }
+        Err(_) => {
+            set_last_error("授权校验库内部错误");
+            AuthStatus::Internal
+        }
+    }
+}
+
+unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(invalid_argument(format!("参数 {} 为空", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| invalid_argument(format!("参数 {} 不是有效的 UTF-8", name)))
+}
+
+unsafe fn read_optional_str<'a>(
+    ptr: *const c_char,
+    name: &str,
+) -> Result<Option<&'a str>, FfiError> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        read_str(ptr, name).map(Some)
+    }
+}
+
+fn read_file(path: &str, what: &str) -> Result<Vec<u8>, FfiError> {
+    fs::read(path).map_err(|e| {
+        FfiError(
+            AuthStatus::MissingFile,
+            format!("读取{} {} 失败: {}", what, path, e),
+        )
+    })
+}
+
+fn to_c_string(value: &str) -> *mut c_char {
+    CString::new(value.replace('\0', " "))
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+fn verify_into(
+    license_bytes: &[u8],
+    device_code: &str,
+    verifier_keys_json: &str,
+    product_id: Option<&str>,
+    clock_skew_secs: i64,
+    out_license: *mut *mut AuthLicense,
+) -> Result<(), FfiError> {
+    let keys = VerifierKeySet::from_json(verifier_keys_json)
+        .map_err(|e| FfiError(AuthStatus::KeyUnavailable, e))?;
+    let mut options = VerifyOptions::default();
+    if let Some(product_id) = product_id {
+        options.product_id = product_id.to_string();
+    }
+    if clock_skew_secs >= 0 {
+        options.clock_skew =
+            verify::clock_skew_from_secs(clock_skew_secs).map_err(invalid_argument)?;
+    }
+    let verified = verify::verify_license(license_bytes, device_code, &keys, &options)?;
+    let license = Box::new(AuthLicense {
+        data: verified.auth_data,
+        fingerprint_match: verified.fingerprint_match,
+    });
+    unsafe { *out_license = Box::into_raw(license) };
+    Ok(())
+}
+
+/// 返回 C 接口版本，接口有不兼容修改时递增；调用方应与自己适配的版本比对，不一致时拒绝使用
+#[no_mangle]
+pub extern "C" fn auth_abi_version() -> u32 {
+    AUTH_ABI_VERSION
+}
+
+/// 校验内存中的授权文件内容。
+///
+/// device_code 为客户端设备码，verifier_keys_json 为 verifier_keys.json 的内容，
+/// product_id 可为 NULL (使用默认产品标识)。clock_skew_secs 为校验有效期时允许的时钟偏差，
+/// 取值 0..=86400 秒，传负数使用默认值 (300 秒)。授权记录了硬件指纹时与本机硬件比对。
+/// 成功时 *out_license 指向新分配的授权句柄。
+///
+/// # Safety
+/// license_data 须指向至少 license_len 字节的可读内存；字符串参数须为以 NUL 结尾的 UTF-8；
+/// out_license 须为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_verify_buffer(
+    license_data: *const u8,
+    license_len: usize,
+    device_code: *const c_char,
+    verifier_keys_json: *const c_char,
+    product_id: *const c_char,
+    clock_skew_secs: i64,
+    out_license: *mut *mut AuthLicense,
+) -> AuthStatus {
+    run_guarded(|| {
+        if license_data.is_null() || out_license.is_null() {
+            return Err(invalid_argument("参数 license_data 或 out_license 为空"));
+        }
+        *out_license = ptr::null_mut();
+        let license_bytes = std::slice::from_raw_parts(license_data, license_len);
+        verify_into(
+            license_bytes,
+            read_str(device_code, "device_code")?,
+            read_str(verifier_keys_json, "verifier_keys_json")?,
+            read_optional_str(product_id, "product_id")?,
+            clock_skew_secs,
+            out_license,
+        )
+    })
+}
+
+/// 校验磁盘上的授权文件，参数均为文件路径 (license.lic、device_code.bin、verifier_keys.json)。
+/// product_id 与 clock_skew_secs 同 auth_verify_buffer。
+///
+/// # Safety
+/// 字符串参数须为以 NUL 结尾的 UTF-8，product_id 可为 NULL；out_license 须为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_verify_file(
+    license_path: *const c_char,
+    device_code_path: *const c_char,
+    verifier_keys_path: *const c_char,
+    product_id: *const c_char,
+    clock_skew_secs: i64,
+    out_license: *mut *mut AuthLicense,
+) -> AuthStatus {
+    run_guarded(|| {
+        if out_license.is_null() {
+            return Err(invalid_argument("参数 out_license 为空"));
+        }
+        *out_license = ptr::null_mut();
+        let license_bytes = read_file(read_str(license_path, "license_path")?, "授权文件")?;
+        let device_code = read_file(
+            read_str(device_code_path, "device_code_path")?,
+            "设备码文件",
+        )?;
+        let keys_json = read_file(
+            read_str(verifier_keys_path, "verifier_keys_path")?,
+            "校验密钥文件",
+        )?;
+        verify_into(
+            &license_bytes,
+            &String::from_utf8_lossy(&device_code),
+            &String::from_utf8_lossy(&keys_json),
+            read_optional_str(product_id, "product_id")?,
+            clock_skew_secs,
+            out_license,
+        )
+    })
+}
+
+/// 返回当前线程最近一次失败的错误信息，没有时返回 NULL；须用 auth_string_free 释放
+#[no_mangle]
+pub extern "C" fn auth_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|message| message.clone().into_raw())
+            .unwrap_or(ptr::null_mut())
+    })
+}
+
+/// 释放本库返回的字符串
+///
+/// # Safety
+/// s 须为本库返回且尚未释放的字符串，或 NULL。
+#[no_mangle]
+pub unsafe extern "C" fn auth_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// 释放授权句柄
+///
+/// # Safety
+/// license 须为 auth_verify_* 返回且尚未释放的句柄，或 NULL。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_free(license: *mut AuthLicense) {
+    if !license.is_null() {
+        drop(Box::from_raw(license));
+    }
+}
+
+/// 授权绑定的设备码；须用 auth_string_free 释放
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_device_code(license: *const AuthLicense) -> *mut c_char {
+    match license.as_ref() {
+        Some(license) => to_c_string(&license.data.device_code),
+        None => ptr::null_mut(),
+    }
+}
+
+/// 授权序列号；须用 auth_string_free 释放
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_serial_number(license: *const AuthLicense) -> *mut c_char {
+    match license.as_ref() {
+        Some(license) => to_c_string(&license.data.serial_number),
+        None => ptr::null_mut(),
+    }
+}
+
+/// 产品版本 ("trial"、"standard"、"professional"、"enterprise")；须用 auth_string_free 释放
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_edition(license: *const AuthLicense) -> *mut c_char {
+    let Some(license) = license.as_ref() else {
+        return ptr::null_mut();
+    };
+    match serde_json::to_value(license.data.edition) {
+        Ok(serde_json::Value::String(edition)) => to_c_string(&edition),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// 签发时间 (Unix 秒)
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_issued_at(license: *const AuthLicense) -> i64 {
+    license
+        .as_ref()
+        .map(|license| license.data.issued_at.timestamp())
+        .unwrap_or(0)
+}
+
+/// 生效时间 (Unix 秒)；授权没有生效时间时返回 false
+///
+/// # Safety
+/// license 须为有效的授权句柄，out_secs 须为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_not_before(
+    license: *const AuthLicense,
+    out_secs: *mut i64,
+) -> bool {
+    match (license.as_ref(), out_secs.is_null()) {
+        (Some(license), false) => {
+            write_optional(license.data.not_before.map(|t| t.timestamp()), out_secs)
+        }
+        _ => false,
+    }
+}
+
+/// 到期时间 (Unix 秒)；永久授权返回 false
+///
+/// # Safety
+/// license 须为有效的授权句柄，out_secs 须为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_expires_at(
+    license: *const AuthLicense,
+    out_secs: *mut i64,
+) -> bool {
+    match (license.as_ref(), out_secs.is_null()) {
+        (Some(license), false) => {
+            write_optional(license.data.expires_at.map(|t| t.timestamp()), out_secs)
+        }
+        _ => false,
+    }
+}
+
+/// 功能是否开启 (开关为 true 或数量上限大于 0)
+///
+/// # Safety
+/// license 须为有效的授权句柄，name 须为以 NUL 结尾的 UTF-8。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_has_feature(
+    license: *const AuthLicense,
+    name: *const c_char,
+) -> bool {
+    match (license.as_ref(), read_str(name, "name")) {
+        (Some(license), Ok(name)) => license.data.entitlements().has_feature(name),
+        _ => false,
+    }
+}
+
+/// 数量类功能的上限；功能不存在或不是数量类时返回 false
+///
+/// # Safety
+/// license 须为有效的授权句柄，name 须为以 NUL 结尾的 UTF-8，out_limit 须为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_feature_limit(
+    license: *const AuthLicense,
+    name: *const c_char,
+    out_limit: *mut u64,
+) -> bool {
+    match (
+        license.as_ref(),
+        read_str(name, "name"),
+        out_limit.is_null(),
+    ) {
+        (Some(license), Ok(name), false) => {
+            write_optional(license.data.entitlements().feature_limit(name), out_limit)
+        }
+        _ => false,
+    }
+}
+
+/// 完整授权内容的 JSON；须用 auth_string_free 释放
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_to_json(license: *const AuthLicense) -> *mut c_char {
+    match license
+        .as_ref()
+        .map(|license| serde_json::to_string(&license.data))
+    {
+        Some(Ok(json)) => to_c_string(&json),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// 授权回写的申请编号 (按授权申请文件签发时才有)，没有时返回 NULL；须用 auth_string_free 释放
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_request_nonce(license: *const AuthLicense) -> *mut c_char {
+    match license
+        .as_ref()
+        .and_then(|license| license.data.request_nonce.as_deref())
+    {
+        Some(nonce) => to_c_string(nonce),
+        None => ptr::null_mut(),
+    }
+}
+
+/// 硬件指纹比对中已变化的组件名，以逗号分隔，全部匹配时为空字符串；
+/// 授权没有记录硬件指纹时返回 NULL。须用 auth_string_free 释放
+///
+/// # Safety
+/// license 须为有效的授权句柄。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_fingerprint_drifted(
+    license: *const AuthLicense,
+) -> *mut c_char {
+    match license
+        .as_ref()
+        .and_then(|license| license.fingerprint_match.as_ref())
+    {
+        Some(fingerprint_match) => to_c_string(&fingerprint_match.drifted.join(",")),
+        None => ptr::null_mut(),
+    }
+}
+
+/// 把设备码 (如 device_code.bin 的内容) 转换为给人读写的 XXXX-XXXX-XXXX-XXXX-C 形式，
+/// 客户端应按该形式显示设备码，授权端只接受带校验位的设备码。
+/// 成功时 *out_display 须用 auth_string_free 释放
+///
+/// # Safety
+/// device_code 须为以 NUL 结尾的 UTF-8；out_display 须为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_device_code_display(
+    device_code: *const c_char,
+    out_display: *mut *mut c_char,
+) -> AuthStatus {
+    run_guarded(|| {
+        if out_display.is_null() {
+            return Err(invalid_argument("参数 out_display 为空"));
+        }
+        *out_display = ptr::null_mut();
+        let device_code = DeviceCode::parse(read_str(device_code, "device_code")?)
+            .map_err(|e| FfiError(AuthStatus::InvalidDeviceCode, e))?;
+        *out_display = to_c_string(&device_code.to_string());
+        Ok(())
+    })
+}
+
+/// 读取本机硬件信息生成授权申请文件，写入 output_path (通常为 license_request.req)。
+///
+/// customer_reference 可为 NULL。out_nonce 可为 NULL；不为 NULL 时写入申请编号，
+/// 须用 auth_string_free 释放，收到授权后用 auth_license_request_nonce 核对。
+///
+/// # Safety
+/// 字符串参数须为以 NUL 结尾的 UTF-8；out_nonce 须为 NULL 或有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn auth_license_request_create(
+    product_id: *const c_char,
+    client_version: *const c_char,
+    customer_reference: *const c_char,
+    output_path: *const c_char,
+    out_nonce: *mut *mut c_char,
+) -> AuthStatus {
+    run_guarded(|| {
+        if !out_nonce.is_null() {
+            *out_nonce = ptr::null_mut();
+        }
+        let request = LicenseRequest::create(
+            read_str(product_id, "product_id")?,
+            read_str(client_version, "client_version")?,
+            read_optional_str(customer_reference, "customer_reference")?,
+        )
+        .map_err(|e| FfiError(AuthStatus::Internal, e))?;
+        let output_path = read_str(output_path, "output_path")?;
+        let bytes = request
+            .to_file_bytes()
+            .map_err(|e| FfiError(AuthStatus::Internal, e))?;
+        fs::write(output_path, bytes).map_err(|e| {
+            FfiError(
+                AuthStatus::Internal,
+                format!("写入授权申请文件 {} 失败: {}", output_path, e),
+            )
+        })?;
+        if !out_nonce.is_null() {
+            *out_nonce = to_c_string(&request.nonce);
+        }
+        Ok(())
+    })
+}
+
+unsafe fn write_optional<T>(value: Option<T>, out: *mut T) -> bool {
+    match value {
+        Some(value) => {
+            *out = value;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use ed25519_dalek::SigningKey;
+
+    use crate::fingerprint::{DeviceFingerprint, FingerprintComponent};
+    use crate::test_support::{
+        auth_data, seal, signing_key, verifier_keys, DEVICE_CODE, LICENSE_KEY, OTHER_DEVICE_CODE,
+    };
+    use crate::verify::{DEFAULT_PRODUCT_ID, MAX_CLOCK_SKEW_SECS};
+
+    fn keys_json() -> CString {
+        CString::new(serde_json::to_string(&verifier_keys()).unwrap()).unwrap()
+    }
+
+    fn c(value: &str) -> CString {
+        CString::new(value).unwrap()
+    }
+
+    unsafe fn take_string(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let value = CStr::from_ptr(s).to_str().unwrap().to_string();
+        auth_string_free(s);
+        Some(value)
+    }
+
+    fn last_error() -> Option<String> {
+        unsafe { take_string(auth_last_error_message()) }
+    }
+
+    fn verify_with(
+        license: &[u8],
+        device_code: &str,
+        product_id: Option<&str>,
+        clock_skew_secs: i64,
+    ) -> (AuthStatus, *mut AuthLicense) {
+        let device_code = c(device_code);
+        let product_id = product_id.map(c);
+        let mut out = ptr::null_mut();
+        let status = unsafe {
+            auth_verify_buffer(
+                license.as_ptr(),
+                license.len(),
+                device_code.as_ptr(),
+                keys_json().as_ptr(),
+                product_id.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                clock_skew_secs,
+                &mut out,
+            )
+        };
+        (status, out)
+    }
+
+    fn status_of(license: &[u8]) -> AuthStatus {
+        let (status, out) = verify_with(license, DEVICE_CODE, None, -1);
+        assert_eq!(status == AuthStatus::Ok, !out.is_null());
+        unsafe { auth_license_free(out) };
+        status
+    }
+
+    #[test]
+    fn verifies_a_buffer_and_reads_the_license() {
+        let mut data = auth_data();
+        let expires_at = Utc::now() + Duration::days(30);
+        data.expires_at = Some(expires_at);
+        let (status, license) = verify_with(&seal(data, &signing_key()), DEVICE_CODE, None, -1);
+        assert_eq!(status, AuthStatus::Ok);
+        assert!(last_error().is_none());
+
+        unsafe {
+            assert_eq!(
+                take_string(auth_license_device_code(license)).as_deref(),
+                Some(DEVICE_CODE)
+            );
+            assert_eq!(
+                take_string(auth_license_serial_number(license)).as_deref(),
+                Some("serial-1")
+            );
+            assert_eq!(
+                take_string(auth_license_edition(license)).as_deref(),
+                Some("professional")
+            );
+            assert!(auth_license_issued_at(license) > 0);
+            let mut secs = 0;
+            assert!(!auth_license_not_before(license, &mut secs));
+            assert!(auth_license_expires_at(license, &mut secs));
+            assert_eq!(secs, expires_at.timestamp());
+            assert!(auth_license_has_feature(license, c("multiplayer").as_ptr()));
+            assert!(!auth_license_has_feature(license, c("recording").as_ptr()));
+            let mut limit = 0;
+            assert!(auth_license_feature_limit(
+                license,
+                c("max_sessions").as_ptr(),
+                &mut limit
+            ));
+            assert_eq!(limit, 8);
+            assert!(!auth_license_feature_limit(
+                license,
+                c("multiplayer").as_ptr(),
+                &mut limit
+            ));
+            assert_eq!(
+                take_string(auth_license_request_nonce(license)).as_deref(),
+                Some("00112233445566778899aabbccddeeff")
+            );
+            assert!(auth_license_fingerprint_drifted(license).is_null());
+            let json = take_string(auth_license_to_json(license)).unwrap();
+            assert!(json.contains(DEFAULT_PRODUCT_ID));
+            auth_license_free(license);
+        }
+    }
+
+    #[test]
+    fn verifies_files_on_disk() {
+        let dir = std::env::temp_dir().join(format!("ffi_verify_file_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let license_path = dir.join("license.lic");
+        let device_code_path = dir.join("device_code.bin");
+        let keys_path = dir.join("verifier_keys.json");
+        fs::write(&license_path, seal(auth_data(), &signing_key())).unwrap();
+        fs::write(&device_code_path, DEVICE_CODE).unwrap();
+        fs::write(&keys_path, keys_json().as_bytes()).unwrap();
+        let path = |p: &std::path::Path| c(p.to_str().unwrap());
+
+        let mut license = ptr::null_mut();
+        let status = unsafe {
+            auth_verify_file(
+                path(&license_path).as_ptr(),
+                path(&device_code_path).as_ptr(),
+                path(&keys_path).as_ptr(),
+                c(DEFAULT_PRODUCT_ID).as_ptr(),
+                -1,
+                &mut license,
+            )
+        };
+        assert_eq!(status, AuthStatus::Ok);
+        unsafe { auth_license_free(license) };
+
+        let status = unsafe {
+            auth_verify_file(
+                path(&dir.join("missing.lic")).as_ptr(),
+                path(&device_code_path).as_ptr(),
+                path(&keys_path).as_ptr(),
+                ptr::null(),
+                -1,
+                &mut license,
+            )
+        };
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(status, AuthStatus::MissingFile);
+        assert!(license.is_null());
+        assert!(last_error().unwrap().contains("missing.lic"));
+    }
+
+    #[test]
+    fn rejects_null_and_invalid_arguments() {
+        let license = seal(auth_data(), &signing_key());
+        let device_code = c(DEVICE_CODE);
+        let keys = keys_json();
+        let mut out = ptr::null_mut();
+        unsafe {
+            let status = auth_verify_buffer(
+                ptr::null(),
+                0,
+                device_code.as_ptr(),
+                keys.as_ptr(),
+                ptr::null(),
+                -1,
+                &mut out,
+            );
+            assert_eq!(status, AuthStatus::InvalidArgument);
+            assert!(last_error().unwrap().contains("license_data"));
+
+            let status = auth_verify_buffer(
+                license.as_ptr(),
+                license.len(),
+                ptr::null(),
+                keys.as_ptr(),
+                ptr::null(),
+                -1,
+                &mut out,
+            );
+            assert_eq!(status, AuthStatus::InvalidArgument);
+            assert!(last_error().unwrap().contains("device_code"));
+
+            let status = auth_verify_buffer(
+                license.as_ptr(),
+                license.len(),
+                device_code.as_ptr(),
+                keys.as_ptr(),
+                ptr::null(),
+                -1,
+                ptr::null_mut(),
+            );
+            assert_eq!(status, AuthStatus::InvalidArgument);
+
+            let invalid_utf8 = [0xffu8, 0];
+            let status = auth_verify_buffer(
+                license.as_ptr(),
+                license.len(),
+                device_code.as_ptr(),
+                keys.as_ptr(),
+                invalid_utf8.as_ptr().cast(),
+                -1,
+                &mut out,
+            );
+            assert_eq!(status, AuthStatus::InvalidArgument);
+            assert!(last_error().unwrap().contains("UTF-8"));
+
+            let status = auth_verify_file(
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+                -1,
+                &mut out,
+            );
+            assert_eq!(status, AuthStatus::InvalidArgument);
+            assert!(out.is_null());
+        }
+
+        // 时钟偏差超出范围
+        let (status, out) = verify_with(&license, DEVICE_CODE, None, MAX_CLOCK_SKEW_SECS + 1);
+        assert_eq!(status, AuthStatus::InvalidArgument);
+        assert!(out.is_null());
+        assert!(last_error().unwrap().contains("时钟偏差"));
+    }
+
+    #[test]
+    fn maps_verification_failures_to_statuses() {
+        let license = seal(auth_data(), &signing_key());
+
+        let (status, _) = verify_with(&license, OTHER_DEVICE_CODE, None, -1);
+        assert_eq!(status, AuthStatus::BindingMismatch);
+        let (status, _) = verify_with(&license, DEVICE_CODE, Some("other-product"), -1);
+        assert_eq!(status, AuthStatus::BindingMismatch);
+        let (status, _) = verify_with(&license, "not a device code", None, -1);
+        assert_eq!(status, AuthStatus::InvalidDeviceCode);
+
+        assert_eq!(
+            status_of(&license[..license.len() - 1]),
+            AuthStatus::BadEncoding
+        );
+        assert_eq!(
+            status_of(b"bm90IGEgbGljZW5zZQ==:AAAAAAAAAAAAAAAA"),
+            AuthStatus::ReissueRequired
+        );
+        assert!(last_error().unwrap().contains("重新签发"));
+        let other_key = SigningKey::from_bytes(&[3; 32]);
+        assert_eq!(
+            status_of(&seal(auth_data(), &other_key)),
+            AuthStatus::Tampered
+        );
+
+        let mut expired = auth_data();
+        expired.expires_at = Some(Utc::now() - Duration::seconds(400));
+        let expired = seal(expired, &signing_key());
+        assert_eq!(status_of(&expired), AuthStatus::Expired);
+        let (status, out) = verify_with(&expired, DEVICE_CODE, None, 0);
+        assert_eq!(status, AuthStatus::Expired);
+        assert!(out.is_null());
+        // 允许的时钟偏差大于过期时长时仍然有效
+        let (status, out) = verify_with(&expired, DEVICE_CODE, None, 600);
+        assert_eq!(status, AuthStatus::Ok);
+        unsafe { auth_license_free(out) };
+
+        let mut not_yet_valid = auth_data();
+        not_yet_valid.not_before = Some(Utc::now() + Duration::days(1));
+        assert_eq!(
+            status_of(&seal(not_yet_valid, &signing_key())),
+            AuthStatus::NotYetValid
+        );
+
+        // 授权记录的指纹与本机不同 (或本机读不到硬件信息) 时不能放行
+        let mut fingerprinted = auth_data();
+        fingerprinted.fingerprint = Some(DeviceFingerprint {
+            threshold_percent: 60,
+            components: vec![FingerprintComponent::new(
+                "machine_id",
+                3,
+                "another machine",
+            )],
+        });
+        assert_eq!(
+            status_of(&seal(fingerprinted, &signing_key())),
+            AuthStatus::FingerprintMismatch
+        );
+
+        let no_keys = serde_json::to_string(&VerifierKeySet {
+            license_key: hex::encode(LICENSE_KEY),
+            keys: Vec::new(),
+        })
+        .unwrap();
+        for (keys_json, expected) in [
+            ("{}", AuthStatus::KeyUnavailable),
+            (no_keys.as_str(), AuthStatus::WrongKey),
+        ] {
+            let mut out = ptr::null_mut();
+            let status = unsafe {
+                auth_verify_buffer(
+                    license.as_ptr(),
+                    license.len(),
+                    c(DEVICE_CODE).as_ptr(),
+                    c(keys_json).as_ptr(),
+                    ptr::null(),
+                    -1,
+                    &mut out,
+                )
+            };
+            assert_eq!(status, expected);
+            assert!(out.is_null());
+        }
+    }
+
+    #[test]
+    fn status_codes_match_the_error_kinds() {
+        for (kind, code) in [
+            (VerifyErrorKind::MissingFile, 1),
+            (VerifyErrorKind::KeyUnavailable, 2),
+            (VerifyErrorKind::BadEncoding, 3),
+            (VerifyErrorKind::WrongLength, 4),
+            (VerifyErrorKind::WrongKey, 5),
+            (VerifyErrorKind::Tampered, 6),
+            (VerifyErrorKind::DeviceMismatch, 7),
+            (VerifyErrorKind::BindingMismatch, 8),
+            (VerifyErrorKind::NotYetValid, 9),
+            (VerifyErrorKind::Expired, 10),
+            (VerifyErrorKind::FingerprintMismatch, 11),
+            (VerifyErrorKind::InvalidDeviceCode, 12),
+            (VerifyErrorKind::ReissueRequired, 13),
+        ] {
+            assert_eq!(AuthStatus::from(kind) as i32, code, "{:?}", kind);
+        }
+        assert_eq!(auth_abi_version(), AUTH_ABI_VERSION);
+    }
+
+    #[test]
+    fn formats_device_codes_for_display() {
+        let mut display = ptr::null_mut();
+        unsafe {
+            let status = auth_device_code_display(c(DEVICE_CODE).as_ptr(), &mut display);
+            assert_eq!(status, AuthStatus::Ok);
+            assert_eq!(
+                take_string(display).as_deref(),
+                Some(DeviceCode::parse(DEVICE_CODE).unwrap().to_string().as_str())
+            );
+
+            let status = auth_device_code_display(c("fb95").as_ptr(), &mut display);
+            assert_eq!(status, AuthStatus::InvalidDeviceCode);
+            assert!(display.is_null());
+            assert!(last_error().unwrap().contains("长度错误"));
+
+            let status = auth_device_code_display(c(DEVICE_CODE).as_ptr(), ptr::null_mut());
+            assert_eq!(status, AuthStatus::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn accessors_and_free_functions_accept_null() {
+        let mut secs = 0;
+        let mut limit = 0;
+        unsafe {
+            auth_license_free(ptr::null_mut());
+            auth_string_free(ptr::null_mut());
+            assert!(auth_license_device_code(ptr::null()).is_null());
+            assert!(auth_license_serial_number(ptr::null()).is_null());
+            assert!(auth_license_edition(ptr::null()).is_null());
+            assert!(auth_license_to_json(ptr::null()).is_null());
+            assert!(auth_license_request_nonce(ptr::null()).is_null());
+            assert!(auth_license_fingerprint_drifted(ptr::null()).is_null());
+            assert_eq!(auth_license_issued_at(ptr::null()), 0);
+            assert!(!auth_license_not_before(ptr::null(), &mut secs));
+            assert!(!auth_license_expires_at(ptr::null(), &mut secs));
+            assert!(!auth_license_has_feature(
+                ptr::null(),
+                c("multiplayer").as_ptr()
+            ));
+            assert!(!auth_license_feature_limit(
+                ptr::null(),
+                c("max_sessions").as_ptr(),
+                &mut limit
+            ));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::verify_error::{VerifyError, VerifyErrorKind};
+
+// 签发时未指定时，匹配的组件权重至少占总权重的 60%
+pub const DEFAULT_MATCH_THRESHOLD_PERCENT: u8 = 60;
+
+const COMPONENT_DIGEST_DOMAIN: &str = "authorization-fingerprint-v1";
+const COMPONENT_DIGEST_LEN: usize = 16;
+
+// 各平台采集的组件名；授权申请只接受这些组件，每个组件权重不超过 MAX_COMPONENT_WEIGHT
+pub(crate) const KNOWN_COMPONENTS: &[&str] = &[
+    "machine_id",
+    "mac_address",
+    "baseboard_serial",
+    "bios_serial",
+    "disk_serial",
+];
+pub(crate) const MAX_COMPONENT_WEIGHT: u32 = 10;
+
+// 单个硬件组件：授权文件中只保存摘要，不保存原始序列号
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintComponent {
+    pub name: String,
+    pub weight: u32,
+    pub digest: String,
+}
+
+impl FingerprintComponent {
+    pub fn new(name: &str, weight: u32, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            weight,
+            digest: component_digest(name, value),
+        }
+    }
+
+    // 检查来自客户端的组件：已知组件名、权重范围与摘要格式
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !KNOWN_COMPONENTS.contains(&self.name.as_str()) {
+            return Err(format!("未知的硬件组件: {}", self.name));
+        }
+        if !(1..=MAX_COMPONENT_WEIGHT).contains(&self.weight) {
+            return Err(format!(
+                "硬件组件 {} 的权重 {} 超出范围 (1..={})",
+                self.name, self.weight, MAX_COMPONENT_WEIGHT
+            ));
+        }
+        let valid_digest = self.digest.len() == COMPONENT_DIGEST_LEN
+            && self
+                .digest
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+        if !valid_digest {
+            return Err(format!("硬件组件 {} 的摘要无效", self.name));
+        }
+        Ok(())
+    }
+}
+
+// 设备的多组件指纹；threshold_percent 由签发端决定，随授权一起签名
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceFingerprint {
+    pub threshold_percent: u8,
+    pub components: Vec<FingerprintComponent>,
+}
+
+// 指纹比对结果：drifted 为授权中记录、但当前设备上已变化或缺失的组件
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintMatch {
+    pub matched_weight: u64,
+    pub total_weight: u64,
+    pub threshold_percent: u8,
+    pub drifted: Vec<String>,
+}
+
+impl FingerprintMatch {
+    pub fn accepted(&self) -> bool {
+        self.total_weight > 0
+            && u128::from(self.matched_weight) * 100
+                >= u128::from(self.threshold_percent) * u128::from(self.total_weight)
+    }
+}
+
+impl DeviceFingerprint {
+    // 以授权中记录的指纹为准，逐个组件与当前设备比对并累计权重
+    pub fn compare(&self, live: &DeviceFingerprint) -> FingerprintMatch {
+        let mut result = FingerprintMatch {
+            matched_weight: 0,
+            total_weight: 0,
+            threshold_percent: self.threshold_percent,
+            drifted: Vec::new(),
+        };
+        for expected in &self.components {
+            result.total_weight += u64::from(expected.weight);
+            let matched = live
+                .components
+                .iter()
+                .any(|c| c.name == expected.name && c.digest == expected.digest);
+            if matched {
+                result.matched_weight += u64::from(expected.weight);
+            } else {
+                result.drifted.push(expected.name.clone());
+            }
+        }
+        result
+    }
+
+    // 比对并在未达到阈值时返回 FingerprintMismatch
+    pub fn check(&self, live: &DeviceFingerprint) -> Result<FingerprintMatch, VerifyError> {
+        let result = self.compare(live);
+        if result.accepted() {
+            Ok(result)
+        } else {
+            Err(VerifyError::new(
+                VerifyErrorKind::FingerprintMismatch,
+                format!(
+                    "设备硬件指纹匹配度不足 ({}/{}，要求 {}%)，已变化的组件: {}",
+                    result.matched_weight,
+                    result.total_weight,
+                    result.threshold_percent,
+                    result.drifted.join(", ")
+                ),
+            ))
+        }
+    }
+}
+
+fn component_digest(name: &str, value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        format!(
+            "{}\n{}\n{}",
+            COMPONENT_DIGEST_DOMAIN,
+            name,
+            value.trim().to_lowercase()
+        )
+        .as_bytes(),
+    );
+    hex::encode(hasher.finalize())[..COMPONENT_DIGEST_LEN].to_string()
+}
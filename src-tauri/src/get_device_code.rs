@@ -1,41 +1,204 @@
-// use sha2::{Digest, Sha256};
-// use std::process::Command;
-// use std::str;
-// 
-// fn get_motherboard_serial() -> Option<String> {
-//     let output = Command::new("wmic")
-//         .args(&["baseboard", "get", "serialnumber"])
-//         .output();
-// 
-//     match output {
-//         Ok(out) => {
-//             if out.status.success() {
-//                 let stdout = str::from_utf8(&out.stdout).unwrap_or_default(); 
-//                 let serial_number = stdout.lines().nth(1).unwrap_or("").trim();
-//                 if !serial_number.is_empty() && serial_number != "To be filled by O.E.M." {
-//                     return Some(serial_number.to_string());
-//                 }
-//             }
-//             None
-//         }
-//         Err(_) => None,
-//     }
-// }
-// 
-// pub fn generate_device_code() -> Result<String, String> {
-//     if let Some(serial_number) = get_motherboard_serial() {
-//         let mut hasher = Sha256::new();
-//         hasher.update(serial_number.as_bytes());
-//         let result = hasher.finalize();
-//         let device_code_full = result
-//             .iter()
-//             .map(|b| format!("{:02x}", b))
-//             .collect::<String>();
-//         let device_code = &device_code_full[..16]; // 取前16位作为设备码
-//         
-// 
-//         Ok(device_code.to_string())
-//     } else {
-//         Err("无法获取主板序列号或序列号无效".to_string())
-//     }
-// }
\ No newline at end of file
+use sha2::{Digest, Sha256};
+use std::process::Command;
+
+use crate::fingerprint::{DeviceFingerprint, FingerprintComponent};
+
+// 主板/BIOS 未填写时常见的占位值，不能作为设备标识
+const PLACEHOLDER_VALUES: &[&str] = &[
+    "to be filled by o.e.m.",
+    "default string",
+    "system serial number",
+    "not applicable",
+    "not specified",
+    "none",
+    "0",
+    "00000000-0000-0000-0000-000000000000",
+    "ffffffff-ffff-ffff-ffff-ffffffffffff",
+    "00:00:00:00:00:00",
+];
+
+// 单个硬件标识；weight 越大表示越稳定、越能区分设备
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct HardwareComponent {
+    pub name: &'static str,
+    pub weight: u32,
+    pub value: String,
+}
+
+// 设备指纹的来源，不同平台读取不同的硬件标识
+pub(crate) trait FingerprintProvider {
+    fn name(&self) -> &'static str;
+
+    // 读取失败或为占位值的组件直接跳过
+    fn components(&self) -> Vec<HardwareComponent>;
+}
+
+// 本机生成的设备码及其组件指纹
+#[derive(Debug, Clone)]
+pub(crate) struct LocalDevice {
+    pub device_code: String,
+    pub fingerprint: DeviceFingerprint,
+}
+
+// Linux：systemd machine-id、首个物理网卡 MAC (网卡最容易更换，权重最低)。
+// 不读取 DMI 产品 UUID：它通常只有 root 可读，会让设备码随运行权限变化
+pub(crate) struct LinuxProvider;
+
+impl FingerprintProvider for LinuxProvider {
+    fn name(&self) -> &'static str {
+        "linux"
+    }
+
+    fn components(&self) -> Vec<HardwareComponent> {
+        let machine_id =
+            read_trimmed("/etc/machine-id").or_else(|| read_trimmed("/var/lib/dbus/machine-id"));
+        [
+            ("machine_id", 3, machine_id),
+            ("mac_address", 1, first_physical_mac()),
+        ]
+        .into_iter()
+        .filter_map(|(name, weight, value)| {
+            value.map(|value| HardwareComponent {
+                name,
+                weight,
+                value,
+            })
+        })
+        .collect()
+    }
+}
+
+// Windows：通过 WMI 读取主板、BIOS 与系统盘序列号 (硬盘最容易更换，权重最低)
+pub(crate) struct WindowsProvider;
+
+impl FingerprintProvider for WindowsProvider {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn components(&self) -> Vec<HardwareComponent> {
+        [
+            ("baseboard_serial", 3, "Win32_BaseBoard", "baseboard"),
+            ("bios_serial", 2, "Win32_BIOS", "bios"),
+            ("disk_serial", 1, "Win32_DiskDrive", "diskdrive"),
+        ]
+        .into_iter()
+        .filter_map(|(name, weight, class, alias)| {
+            wmi_serial(class, alias).map(|value| HardwareComponent {
+                name,
+                weight,
+                value,
+            })
+        })
+        .collect()
+    }
+}
+
+pub(crate) fn default_provider() -> Box<dyn FingerprintProvider + Send> {
+    if cfg!(target_os = "windows") {
+        Box::new(WindowsProvider)
+    } else {
+        Box::new(LinuxProvider)
+    }
+}
+
+// 用当前平台的指纹来源生成本机设备码与指纹
+pub(crate) fn local_device() -> Result<LocalDevice, String> {
+    collect_local_device(default_provider().as_ref())
+}
+
+// 当前设备的硬件指纹；校验时未传入 VerifyOptions::live_fingerprint 则自动读取
+pub fn local_fingerprint() -> Result<DeviceFingerprint, String> {
+    local_device().map(|device| device.fingerprint)
+}
+
+// 读取本机硬件组件，生成设备码和用于容错比对的组件指纹
+pub(crate) fn collect_local_device(
+    provider: &dyn FingerprintProvider,
+) -> Result<LocalDevice, String> {
+    let mut components = provider.components();
+    if components.is_empty() {
+        return Err(format!(
+            "无法从 {} 平台读取任何硬件标识，无法生成设备码",
+            provider.name()
+        ));
+    }
+    components.sort();
+    Ok(LocalDevice {
+        device_code: device_code_of(&components),
+        fingerprint: DeviceFingerprint {
+            threshold_percent: crate::fingerprint::DEFAULT_MATCH_THRESHOLD_PERCENT,
+            components: components
+                .iter()
+                .map(|c| FingerprintComponent::new(c.name, c.weight, &c.value))
+                .collect(),
+        },
+    })
+}
+
+// 组件按名称排序后以 "名称=值" 逐行拼接做 SHA-256，取前 16 位十六进制作为设备码
+fn device_code_of(components: &[HardwareComponent]) -> String {
+    let mut hasher = Sha256::new();
+    for component in components {
+        hasher
+            .update(format!("{}={}\n", component.name, component.value.to_lowercase()).as_bytes());
+    }
+    let device_code_full = hex::encode(hasher.finalize());
+    device_code_full[..16].to_string() // 取前16位作为设备码
+}
+
+fn usable(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() || PLACEHOLDER_VALUES.contains(&value.to_lowercase().as_str()) {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    std::fs::read_to_string(path).ok().and_then(|s| usable(&s))
+}
+
+// 跳过回环和虚拟网卡 (没有 device 链接的接口)，按接口名取第一个
+fn first_physical_mac() -> Option<String> {
+    let mut interfaces: Vec<_> = std::fs::read_dir("/sys/class/net")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("device").exists())
+        .collect();
+    interfaces.sort_by_key(|entry| entry.file_name());
+    interfaces
+        .iter()
+        .find_map(|entry| read_trimmed(&entry.path().join("address").to_string_lossy()))
+}
+
+// 优先使用 wmic，新版 Windows 已移除 wmic 时改用 PowerShell 的 Get-CimInstance
+fn wmi_serial(class: &str, wmic_alias: &str) -> Option<String> {
+    let wmic = run_hidden("wmic", &[wmic_alias, "get", "serialnumber"])
+        .and_then(|stdout| stdout.lines().skip(1).find_map(usable));
+    wmic.or_else(|| {
+        let script = format!(
+            "(Get-CimInstance -ClassName {} | Select-Object -First 1).SerialNumber",
+            class
+        );
+        run_hidden("powershell", &["-NoProfile", "-Command", &script])
+            .and_then(|stdout| stdout.lines().find_map(usable))
+    })
+}
+
+fn run_hidden(program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,98 @@
+#[cfg(any(test, feature = "app"))]
+use argon2::Argon2;
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, TryRngCore};
+use sha2::Sha256;
+
+// 原始密钥 (如授权密钥) 至少 32 字节，口令至少 8 个字符
+pub(crate) const MIN_RAW_KEY_LEN: usize = 32;
+#[cfg(any(test, feature = "app"))]
+pub(crate) const MIN_PASSPHRASE_LEN: usize = 8;
+#[cfg(any(test, feature = "app"))]
+pub(crate) const SALT_LEN: usize = 16;
+
+const LICENSE_ENCRYPTION_INFO: &[u8] = b"authorization-license-aes-256-gcm";
+const LICENSE_MAC_INFO: &[u8] = b"authorization-license-mac-key";
+
+// 授权文件头部记录的密钥派生算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfAlgorithm {
+    HkdfSha256 = 1,
+}
+
+impl KdfAlgorithm {
+    pub(crate) fn from_byte(value: u8) -> Result<Self, String> {
+        match value {
+            1 => Ok(Self::HkdfSha256),
+            other => Err(format!("不支持的密钥派生算法: {}", other)),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn random_salt() -> Result<[u8; SALT_LEN], String> {
+    random_bytes::<SALT_LEN>()
+}
+
+// 由原始授权密钥派生单个授权文件的 AES-256-GCM 密钥
+pub(crate) fn derive_license_encryption_key(
+    algorithm: KdfAlgorithm,
+    raw_key: &[u8],
+    salt: &[u8],
+) -> Result<[u8; 32], String> {
+    check_raw_key(raw_key)?;
+    match algorithm {
+        KdfAlgorithm::HkdfSha256 => hkdf_expand(raw_key, Some(salt), LICENSE_ENCRYPTION_INFO),
+    }
+}
+
+// 由原始授权密钥派生 MAC 密钥，与加密密钥相互独立
+pub(crate) fn derive_mac_key(raw_key: &[u8]) -> Result<[u8; 32], String> {
+    check_raw_key(raw_key)?;
+    hkdf_expand(raw_key, None, LICENSE_MAC_INFO)
+}
+
+// 由操作员口令派生密钥 (Argon2id)
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn derive_passphrase_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("口令派生密钥失败: {}", e))?;
+    Ok(key)
+}
+
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn check_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("口令至少需要 {} 个字符", MIN_PASSPHRASE_LEN));
+    }
+    Ok(())
+}
+
+fn check_raw_key(raw_key: &[u8]) -> Result<(), String> {
+    if raw_key.len() < MIN_RAW_KEY_LEN {
+        return Err(format!(
+            "密钥过短 ({} 字节)，至少需要 {} 字节",
+            raw_key.len(),
+            MIN_RAW_KEY_LEN
+        ));
+    }
+    Ok(())
+}
+
+fn hkdf_expand(ikm: &[u8], salt: Option<&[u8]>, info: &[u8]) -> Result<[u8; 32], String> {
+    let mut okm = [0u8; 32];
+    Hkdf::<Sha256>::new(salt, ikm)
+        .expand(info, &mut okm)
+        .map_err(|e| format!("HKDF 派生失败: {}", e))?;
+    Ok(okm)
+}
+
+pub(crate) fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    OsRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|e| format!("无法生成随机数: {}", e))?;
+    Ok(bytes)
+}
@@ -0,0 +1,443 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::kdf::{self, random_bytes};
+use crate::verifier_keys::{VerifierKey, VerifierKeySet};
+
+pub(crate) const KEYSTORE_FILE_NAME: &str = "keystore.json";
+pub(crate) const VERIFIER_KEYS_FILE_NAME: &str = "verifier_keys.json";
+const KEYSTORE_VERSION: u32 = 1;
+
+// 密钥库文件：签名私钥以口令派生的密钥加密保存，其余字段为公开信息
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct KeystoreFile {
+    version: u32,
+    salt: String,        // base64，口令派生 (Argon2id) 所用盐值
+    license_key: String, // hex，授权文件 AES-256-GCM 密钥，校验端同样持有
+    active_key_id: String,
+    keys: Vec<KeystoreEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct KeystoreEntry {
+    key_id: String,
+    created_at: DateTime<Utc>,
+    public_key: String,    // hex
+    sealed_secret: String, // base64(ciphertext)
+    nonce: String,         // base64
+}
+
+// 签发授权时使用的当前密钥
+#[derive(Clone)]
+pub struct IssuingKey {
+    pub key_id: String,
+    pub signing_key: SigningKey,
+    pub license_key: Vec<u8>,
+}
+
+// 已用口令解锁的密钥库
+pub struct UnlockedKeystore {
+    path: PathBuf,
+    wrapping_key: [u8; 32],
+    file: KeystoreFile,
+    signing_keys: HashMap<String, SigningKey>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeystoreStatus {
+    pub exists: bool,
+    pub unlocked: bool,
+    pub active_key_id: Option<String>,
+    pub key_ids: Vec<String>,
+}
+
+impl UnlockedKeystore {
+    // 打开密钥库；不存在时用该口令新建，并生成第一把签名密钥
+    pub(crate) fn open_or_create(key_dir: &Path, passphrase: &str) -> Result<Self, String> {
+        let path = key_dir.join(KEYSTORE_FILE_NAME);
+        if path.exists() {
+            Self::open(path, passphrase)
+        } else {
+            Self::create(key_dir, path, passphrase)
+        }
+    }
+
+    fn open(path: PathBuf, passphrase: &str) -> Result<Self, String> {
+        let file = read_keystore_file(&path)?;
+        if file.version != KEYSTORE_VERSION {
+            return Err(format!("不支持的密钥库版本: {}", file.version));
+        }
+        let salt = STANDARD
+            .decode(&file.salt)
+            .map_err(|e| format!("密钥库盐值解码失败: {}", e))?;
+        let wrapping_key = kdf::derive_passphrase_key(passphrase, &salt)?;
+
+        let mut signing_keys = HashMap::new();
+        for entry in &file.keys {
+            let seed = unseal_secret(entry, &wrapping_key)?;
+            let signing_key = SigningKey::from_bytes(&seed);
+            if hex::encode(signing_key.verifying_key().to_bytes()) != entry.public_key {
+                return Err(format!("密钥 {} 的公钥与私钥不匹配", entry.key_id));
+            }
+            signing_keys.insert(entry.key_id.clone(), signing_key);
+        }
+        if !signing_keys.contains_key(&file.active_key_id) {
+            return Err(format!("密钥库中不存在当前密钥: {}", file.active_key_id));
+        }
+
+        Ok(Self {
+            path,
+            wrapping_key,
+            file,
+            signing_keys,
+        })
+    }
+
+    fn create(key_dir: &Path, path: PathBuf, passphrase: &str) -> Result<Self, String> {
+        kdf::check_passphrase(passphrase).map_err(|e| format!("新建密钥库失败: {}", e))?;
+        fs::create_dir_all(key_dir)
+            .map_err(|e| format!("创建密钥目录 {} 失败: {}", key_dir.display(), e))?;
+
+        let salt = kdf::random_salt()?;
+        let license_key = random_bytes::<{ kdf::MIN_RAW_KEY_LEN }>()?;
+        let wrapping_key = kdf::derive_passphrase_key(passphrase, &salt)?;
+
+        let mut keystore = Self {
+            path,
+            wrapping_key,
+            file: KeystoreFile {
+                version: KEYSTORE_VERSION,
+                salt: STANDARD.encode(salt),
+                license_key: hex::encode(license_key),
+                active_key_id: String::new(),
+                keys: Vec::new(),
+            },
+            signing_keys: HashMap::new(),
+        };
+        keystore.rotate()?;
+        Ok(keystore)
+    }
+
+    // 生成新的签名密钥并设为当前密钥；旧密钥保留用于校验已签发的授权
+    pub(crate) fn rotate(&mut self) -> Result<String, String> {
+        let seed = random_bytes::<32>()?;
+        let signing_key = SigningKey::from_bytes(&seed);
+        let created_at = Utc::now();
+        let key_id = format!(
+            "{}-{}",
+            created_at.format("%Y%m%d"),
+            hex::encode(random_bytes::<4>()?)
+        );
+
+        let nonce_bytes = random_bytes::<12>()?;
+        let cipher = Aes256Gcm::new((&self.wrapping_key).into());
+        let sealed = cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), seed.as_slice())
+            .map_err(|_| "签名私钥加密失败".to_string())?;
+
+        // 先把新密钥写入磁盘，写入失败时内存中的密钥库保持不变
+        let mut file = self.file.clone();
+        file.keys.push(KeystoreEntry {
+            key_id: key_id.clone(),
+            created_at,
+            public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+            sealed_secret: STANDARD.encode(sealed),
+            nonce: STANDARD.encode(nonce_bytes),
+        });
+        file.active_key_id = key_id.clone();
+        save(&self.path, &file)?;
+        self.file = file;
+        self.signing_keys.insert(key_id.clone(), signing_key);
+        Ok(key_id)
+    }
+
+    pub(crate) fn active_key(&self) -> Result<IssuingKey, String> {
+        self.issuing_key(&self.file.active_key_id)
+            .map_err(|_| format!("密钥库中不存在当前密钥: {}", self.file.active_key_id))
+    }
+
+    // 按密钥 ID 取签发密钥，用于仍内置旧公钥的客户端
+    pub(crate) fn issuing_key(&self, key_id: &str) -> Result<IssuingKey, String> {
+        let signing_key = self
+            .signing_keys
+            .get(key_id)
+            .ok_or_else(|| format!("密钥库中不存在密钥: {}", key_id))?;
+        Ok(IssuingKey {
+            key_id: key_id.to_string(),
+            signing_key: signing_key.clone(),
+            license_key: self.verifier_keys().license_key_bytes()?,
+        })
+    }
+
+    pub(crate) fn status(&self) -> KeystoreStatus {
+        KeystoreStatus {
+            exists: true,
+            unlocked: true,
+            active_key_id: Some(self.file.active_key_id.clone()),
+            key_ids: self.file.keys.iter().map(|k| k.key_id.clone()).collect(),
+        }
+    }
+
+    fn verifier_keys(&self) -> VerifierKeySet {
+        verifier_keys_of(&self.file)
+    }
+}
+
+// 保存密钥库，并同步导出客户端使用的校验密钥文件。
+// 先写校验密钥：它只比旧密钥库多出新公钥，此时失败两个文件都保持原样；
+// 随后写密钥库失败时恢复原来的校验密钥文件，磁盘与内存中的密钥库保持一致
+fn save(path: &Path, file: &KeystoreFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    let verifier_path = path.with_file_name(VERIFIER_KEYS_FILE_NAME);
+    let verifier_json =
+        serde_json::to_string_pretty(&verifier_keys_of(file)).map_err(|e| e.to_string())?;
+
+    let previous_verifier = fs::read(&verifier_path).ok();
+    write_atomic(&verifier_path, verifier_json.as_bytes())
+        .map_err(|e| format!("写入校验密钥 {} 失败: {}", verifier_path.display(), e))?;
+    write_atomic(path, json.as_bytes()).map_err(|e| {
+        let _ = match &previous_verifier {
+            Some(previous) => write_atomic(&verifier_path, previous),
+            None => fs::remove_file(&verifier_path),
+        };
+        format!("写入密钥库 {} 失败: {}", path.display(), e)
+    })
+}
+
+// 先写入同目录下的临时文件并刷盘，再重命名覆盖目标；中途崩溃时原文件保持完整
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut temp| {
+            temp.write_all(content)?;
+            temp.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// 读取校验密钥（只读取公开部分，无需口令）
+pub(crate) fn load_verifier_keys(key_dir: &Path) -> Result<VerifierKeySet, String> {
+    let path = key_dir.join(KEYSTORE_FILE_NAME);
+    if !path.exists() {
+        return Err(format!("未找到密钥库: {}", path.display()));
+    }
+    Ok(verifier_keys_of(&read_keystore_file(&path)?))
+}
+
+pub(crate) fn locked_status(key_dir: &Path) -> KeystoreStatus {
+    match load_verifier_keys(key_dir) {
+        Ok(keys) => KeystoreStatus {
+            exists: true,
+            unlocked: false,
+            active_key_id: None,
+            key_ids: keys.keys.into_iter().map(|k| k.key_id).collect(),
+        },
+        Err(_) => KeystoreStatus {
+            exists: false,
+            unlocked: false,
+            active_key_id: None,
+            key_ids: Vec::new(),
+        },
+    }
+}
+
+fn verifier_keys_of(file: &KeystoreFile) -> VerifierKeySet {
+    VerifierKeySet {
+        license_key: file.license_key.clone(),
+        keys: file
+            .keys
+            .iter()
+            .map(|k| VerifierKey {
+                key_id: k.key_id.clone(),
+                public_key: k.public_key.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn read_keystore_file(path: &Path) -> Result<KeystoreFile, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("读取密钥库 {} 失败: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("密钥库格式错误: {}", e))
+}
+
+fn unseal_secret(entry: &KeystoreEntry, wrapping_key: &[u8; 32]) -> Result<[u8; 32], String> {
+    let sealed = STANDARD
+        .decode(&entry.sealed_secret)
+        .map_err(|e| format!("密钥 {} 解码失败: {}", entry.key_id, e))?;
+    let nonce = STANDARD
+        .decode(&entry.nonce)
+        .map_err(|e| format!("密钥 {} 的 nonce 解码失败: {}", entry.key_id, e))?;
+    if nonce.len() != 12 {
+        return Err(format!("密钥 {} 的 nonce 长度错误", entry.key_id));
+    }
+    let cipher = Aes256Gcm::new(wrapping_key.into());
+    let seed = cipher
+        .decrypt(Nonce::from_slice(&nonce), sealed.as_slice())
+        .map_err(|_| "密钥库口令错误或密钥库已损坏".to_string())?;
+    seed.try_into()
+        .map_err(|_| format!("密钥 {} 长度错误", entry.key_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    fn key_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("keystore_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn exported_verifier_keys(dir: &Path) -> VerifierKeySet {
+        let json = fs::read_to_string(dir.join(VERIFIER_KEYS_FILE_NAME)).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn creates_a_keystore_with_one_signing_key() {
+        let dir = key_dir("create");
+        assert!(!locked_status(&dir).exists);
+        let keystore = UnlockedKeystore::open_or_create(&dir, PASSPHRASE).unwrap();
+        let status = keystore.status();
+        assert_eq!(status.key_ids.len(), 1);
+        assert_eq!(status.active_key_id.as_ref(), Some(&status.key_ids[0]));
+        let locked = locked_status(&dir);
+        assert!(locked.exists && !locked.unlocked);
+        assert_eq!(locked.key_ids, status.key_ids);
+
+        // 导出的校验密钥与密钥库一致，且能校验当前签名密钥
+        let active = keystore.active_key().unwrap();
+        let verifier_keys = load_verifier_keys(&dir).unwrap();
+        assert_eq!(
+            serde_json::to_value(exported_verifier_keys(&dir)).unwrap(),
+            serde_json::to_value(&verifier_keys).unwrap()
+        );
+        assert_eq!(
+            verifier_keys.verifying_key(&active.key_id).unwrap(),
+            active.signing_key.verifying_key()
+        );
+        assert_eq!(active.license_key.len(), kdf::MIN_RAW_KEY_LEN);
+
+        // 短口令不能新建密钥库
+        assert!(UnlockedKeystore::open_or_create(&key_dir("short"), "short").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_a_wrong_passphrase() {
+        let dir = key_dir("passphrase");
+        UnlockedKeystore::open_or_create(&dir, PASSPHRASE).unwrap();
+        let error = UnlockedKeystore::open_or_create(&dir, "wrong passphrase")
+            .err()
+            .unwrap();
+        assert_eq!(error, "密钥库口令错误或密钥库已损坏");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotation_keeps_old_keys_for_verification() {
+        let dir = key_dir("rotate");
+        let mut keystore = UnlockedKeystore::open_or_create(&dir, PASSPHRASE).unwrap();
+        let old = keystore.active_key().unwrap();
+        let new_key_id = keystore.rotate().unwrap();
+        assert_ne!(new_key_id, old.key_id);
+        assert_eq!(keystore.active_key().unwrap().key_id, new_key_id);
+
+        let reopened = UnlockedKeystore::open_or_create(&dir, PASSPHRASE).unwrap();
+        assert_eq!(
+            reopened.status().key_ids,
+            vec![old.key_id.clone(), new_key_id]
+        );
+        assert_eq!(
+            reopened
+                .issuing_key(&old.key_id)
+                .unwrap()
+                .signing_key
+                .to_bytes(),
+            old.signing_key.to_bytes()
+        );
+        assert_eq!(exported_verifier_keys(&dir).keys.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn detects_a_public_key_that_does_not_match_the_secret() {
+        let dir = key_dir("mismatch");
+        let mut keystore = UnlockedKeystore::open_or_create(&dir, PASSPHRASE).unwrap();
+        keystore.rotate().unwrap();
+
+        let path = dir.join(KEYSTORE_FILE_NAME);
+        let mut file = read_keystore_file(&path).unwrap();
+        file.keys[0].public_key = file.keys[1].public_key.clone();
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+
+        let error = UnlockedKeystore::open_or_create(&dir, PASSPHRASE)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            format!("密钥 {} 的公钥与私钥不匹配", file.keys[0].key_id)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_saves_leave_both_files_unchanged() {
+        let dir = key_dir("atomic");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert!(!dir.join("file.json.tmp").exists());
+
+        let mut keystore = UnlockedKeystore::open_or_create(&dir, PASSPHRASE).unwrap();
+        let status = keystore.status();
+        let verifier_json = fs::read(dir.join(VERIFIER_KEYS_FILE_NAME)).unwrap();
+
+        // 把密钥库路径换成目录，重命名覆盖时失败
+        let keystore_path = dir.join(KEYSTORE_FILE_NAME);
+        let keystore_json = fs::read(&keystore_path).unwrap();
+        fs::remove_file(&keystore_path).unwrap();
+        fs::create_dir(&keystore_path).unwrap();
+        assert!(keystore.rotate().is_err());
+
+        assert_eq!(
+            fs::read(dir.join(VERIFIER_KEYS_FILE_NAME)).unwrap(),
+            verifier_json
+        );
+        assert!(!dir.join("keystore.json.tmp").exists());
+        assert_eq!(keystore.status().key_ids, status.key_ids);
+        assert_eq!(keystore.status().active_key_id, status.active_key_id);
+
+        fs::remove_dir(&keystore_path).unwrap();
+        fs::write(&keystore_path, keystore_json).unwrap();
+        assert_eq!(
+            UnlockedKeystore::open_or_create(&dir, PASSPHRASE)
+                .unwrap()
+                .status()
+                .key_ids,
+            status.key_ids
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
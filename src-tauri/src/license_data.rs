@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entitlements::{Edition, Entitlements, FeatureSet};
+use crate::fingerprint::DeviceFingerprint;
+
+// 授权文件中签名保护的授权内容
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuthorizationData {
+    pub device_code: String,
+    pub issued_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>, // None 表示签发即生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>, // None 表示永久授权
+    #[serde(default)]
+    pub edition: Edition,
+    #[serde(default, skip_serializing_if = "FeatureSet::is_empty")]
+    pub features: FeatureSet,
+    // 签发时记录的硬件组件指纹，校验时按阈值容错比对；Android 授权没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<DeviceFingerprint>,
+    // 按授权申请文件签发时回写申请编号，客户端据此确认授权对应自己的申请
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_nonce: Option<String>,
+    // 授权绑定的产品标识，受签名和 MAC 保护；version 3 授权必须包含，只有旧版授权没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    pub serial_number: String,
+    pub checksum: String,
+}
+
+impl AuthorizationData {
+    pub fn entitlements(&self) -> Entitlements {
+        Entitlements {
+            edition: self.edition,
+            features: self.features.clone(),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+use crate::kdf::KdfAlgorithm;
+
+// 授权文件容器格式 (version 3)：
+//   magic(4) | version(1) | algorithm(1) | key_id_len(1) | key_id | kdf(1) | salt_len(1) | salt
+//   | nonce_len(1) | nonce | payload_len(4, 大端) | payload
+// 头部、设备码和产品标识作为 AES-GCM 附加数据。只接受 version 3：早期的 version 1/2 不带附加数据，
+// 持有授权密钥即可把 version 3 的内容重新封装成旧版本绕过绑定。
+// 旧版授权文件为纯文本 "base64(密文):base64(nonce)"，使用已公开的固定密钥且没有签名，
+// 默认拒绝，只有 VerifyOptions::allow_legacy 显式开启时才按旧格式校验。
+pub(crate) const LICENSE_MAGIC: &[u8; 4] = b"XLIC";
+pub(crate) const CURRENT_FORMAT_VERSION: u8 = 3;
+pub const LEGACY_FORMAT_VERSION: u8 = 0; // 旧版文本格式授权在 VerifiedLicense 中记录的格式版本
+
+// 旧版授权使用的固定 AES 密钥，已随客户端公开，只能用于兼容已部署的授权
+pub(crate) const LEGACY_LICENSE_KEY: [u8; 32] = [
+    0x6a, 0x1c, 0x61, 0x09, 0xe2, 0x6c, 0xad, 0x37, 0xf6, 0x29, 0x5b, 0xd3, 0xf3, 0xc2, 0x70, 0x44,
+    0x7f, 0x92, 0x72, 0xc4, 0x31, 0x82, 0x37, 0x68, 0x5b, 0x6c, 0x41, 0x1d, 0x3a, 0x34, 0x35, 0x9e,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseAlgorithm {
+    // AES-256-GCM 加密，内部为 Ed25519 签名的授权数据
+    Aes256GcmEd25519 = 1,
+}
+
+impl LicenseAlgorithm {
+    fn from_byte(value: u8) -> Result<Self, String> {
+        match value {
+            1 => Ok(Self::Aes256GcmEd25519),
+            other => Err(format!("不支持的授权算法: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LicenseContainer {
+    pub version: u8,
+    pub algorithm: LicenseAlgorithm,
+    pub key_id: String,
+    pub kdf: KdfAlgorithm,
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+impl LicenseContainer {
+    // 新建只有头部的容器，nonce 与 payload 在加密后填入
+    #[cfg(any(test, feature = "app"))]
+    pub(crate) fn new(key_id: &str, salt: Vec<u8>) -> Self {
+        Self {
+            version: CURRENT_FORMAT_VERSION,
+            algorithm: LicenseAlgorithm::Aes256GcmEd25519,
+            key_id: key_id.to_string(),
+            kdf: KdfAlgorithm::HkdfSha256,
+            salt,
+            nonce: Vec::new(),
+            payload: Vec::new(),
+        }
+    }
+
+    // AES-GCM 附加数据：头部字段 + 设备码 + 产品标识，防止授权被挪用到其他设备或产品
+    pub(crate) fn associated_data(&self, device_code: &str, product_id: &str) -> Vec<u8> {
+        let mut aad = Vec::new();
+        aad.extend_from_slice(LICENSE_MAGIC);
+        aad.push(self.version);
+        aad.push(self.algorithm as u8);
+        for field in [
+            self.key_id.as_bytes(),
+            &[self.kdf as u8],
+            &self.salt,
+            device_code.as_bytes(),
+            product_id.as_bytes(),
+        ] {
+            aad.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            aad.extend_from_slice(field);
+        }
+        aad
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let key_id = self.key_id.as_bytes();
+        let key_id_len = u8::try_from(key_id.len()).map_err(|_| "密钥 ID 过长".to_string())?;
+        let salt_len = u8::try_from(self.salt.len()).map_err(|_| "盐值过长".to_string())?;
+        let nonce_len = u8::try_from(self.nonce.len()).map_err(|_| "nonce 过长".to_string())?;
+        let payload_len =
+            u32::try_from(self.payload.len()).map_err(|_| "授权数据过长".to_string())?;
+
+        let mut bytes = Vec::with_capacity(
+            14 + key_id.len() + self.salt.len() + self.nonce.len() + self.payload.len(),
+        );
+        bytes.extend_from_slice(LICENSE_MAGIC);
+        bytes.push(self.version);
+        bytes.push(self.algorithm as u8);
+        bytes.push(key_id_len);
+        bytes.extend_from_slice(key_id);
+        bytes.push(self.kdf as u8);
+        bytes.push(salt_len);
+        bytes.extend_from_slice(&self.salt);
+        bytes.push(nonce_len);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&payload_len.to_be_bytes());
+        bytes.extend_from_slice(&self.payload);
+        Ok(bytes)
+    }
+
+    // 解析授权文件容器；旧版两段式文本格式只识别出来并报错
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.starts_with(LICENSE_MAGIC) {
+            Self::parse_container(&bytes[LICENSE_MAGIC.len()..])
+        } else if is_legacy_license(bytes) {
+            Err(LEGACY_REISSUE_MESSAGE.to_string())
+        } else {
+            Err("授权文件格式错误 (缺少文件头)".to_string())
+        }
+    }
+
+    fn parse_container(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let version = reader.u8()?;
+        if version != CURRENT_FORMAT_VERSION {
+            return Err(format!("不支持的授权文件版本: {}", version));
+        }
+        let algorithm = LicenseAlgorithm::from_byte(reader.u8()?)?;
+        let key_id_len = reader.u8()? as usize;
+        let key_id = String::from_utf8(reader.take(key_id_len)?.to_vec())
+            .map_err(|_| "授权文件密钥 ID 不是有效的 UTF-8".to_string())?;
+        let kdf = KdfAlgorithm::from_byte(reader.u8()?)?;
+        let salt_len = reader.u8()? as usize;
+        let salt = reader.take(salt_len)?.to_vec();
+        let nonce_len = reader.u8()? as usize;
+        let nonce = reader.take(nonce_len)?.to_vec();
+        let payload_len = u32::from_be_bytes(reader.take(4)?.try_into().unwrap()) as usize;
+        let payload = reader.take(payload_len)?.to_vec();
+        if reader.pos != bytes.len() {
+            return Err("授权文件末尾存在多余数据".to_string());
+        }
+
+        Ok(Self {
+            version,
+            algorithm,
+            key_id,
+            kdf,
+            salt,
+            nonce,
+            payload,
+        })
+    }
+}
+
+pub(crate) const LEGACY_REISSUE_MESSAGE: &str =
+    "旧版授权文件 (无签名，固定密钥) 已不再支持，请重新签发授权";
+
+// 旧版文本格式授权拆分出的密文和 nonce，用 LEGACY_LICENSE_KEY 解密，没有附加数据
+pub(crate) struct LegacyLicense {
+    pub ciphertext: Vec<u8>,
+    pub nonce: Vec<u8>,
+}
+
+impl LegacyLicense {
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let text = std::str::from_utf8(bytes).map_err(|_| "旧版授权文件不是文本".to_string())?;
+        let (ciphertext, nonce) = text
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| "旧版授权文件格式错误 (缺少分隔符)".to_string())?;
+        let decode = |part: &str| {
+            STANDARD
+                .decode(part)
+                .map_err(|e| format!("旧版授权文件 base64 解码失败: {}", e))
+        };
+        Ok(Self {
+            ciphertext: decode(ciphertext)?,
+            nonce: decode(nonce)?,
+        })
+    }
+}
+
+// 是否为旧版 "base64(密文):base64(nonce)" 文本格式的授权文件
+pub(crate) fn is_legacy_license(bytes: &[u8]) -> bool {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return false;
+    };
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    parts.len() == 2 && parts.iter().all(|part| STANDARD.decode(part).is_ok())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "授权文件被截断".to_string())?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container_bytes() -> Vec<u8> {
+        let mut container = LicenseContainer::new("20240101-test", vec![1; 16]);
+        container.nonce = vec![2; 12];
+        container.payload = vec![3; 40];
+        container.to_bytes().unwrap()
+    }
+
+    // 魔数之后依次为 version、algorithm、key_id_len
+    const VERSION_OFFSET: usize = 4;
+    const ALGORITHM_OFFSET: usize = 5;
+    const KEY_ID_LEN_OFFSET: usize = 6;
+
+    #[test]
+    fn round_trips_the_current_version() {
+        let parsed = LicenseContainer::parse(&container_bytes()).unwrap();
+        assert_eq!(parsed.version, CURRENT_FORMAT_VERSION);
+        assert_eq!(parsed.algorithm, LicenseAlgorithm::Aes256GcmEd25519);
+        assert_eq!(parsed.key_id, "20240101-test");
+        assert_eq!(parsed.kdf, KdfAlgorithm::HkdfSha256);
+        assert_eq!(parsed.salt, vec![1; 16]);
+        assert_eq!(parsed.nonce, vec![2; 12]);
+        assert_eq!(parsed.payload, vec![3; 40]);
+    }
+
+    #[test]
+    fn rejects_every_truncation() {
+        let bytes = container_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                LicenseContainer::parse(&bytes[..len]).is_err(),
+                "截断到 {} 字节仍被接受",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = container_bytes();
+        bytes.push(0);
+        assert_eq!(
+            LicenseContainer::parse(&bytes).unwrap_err(),
+            "授权文件末尾存在多余数据"
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        for version in [0, 1, 2, 4, u8::MAX] {
+            let mut bytes = container_bytes();
+            bytes[VERSION_OFFSET] = version;
+            assert_eq!(
+                LicenseContainer::parse(&bytes).unwrap_err(),
+                format!("不支持的授权文件版本: {}", version)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_algorithms() {
+        let mut bytes = container_bytes();
+        bytes[ALGORITHM_OFFSET] = 2;
+        assert_eq!(
+            LicenseContainer::parse(&bytes).unwrap_err(),
+            "不支持的授权算法: 2"
+        );
+
+        // 旧版 "不派生密钥" (0) 同样不再接受
+        let mut bytes = container_bytes();
+        let kdf_offset = KEY_ID_LEN_OFFSET + 1 + bytes[KEY_ID_LEN_OFFSET] as usize;
+        bytes[kdf_offset] = 0;
+        assert_eq!(
+            LicenseContainer::parse(&bytes).unwrap_err(),
+            "不支持的密钥派生算法: 0"
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_length_fields() {
+        let mut bytes = container_bytes();
+        bytes[KEY_ID_LEN_OFFSET] = u8::MAX;
+        assert_eq!(
+            LicenseContainer::parse(&bytes).unwrap_err(),
+            "授权文件被截断"
+        );
+
+        // payload 长度声明为 4 GiB 时直接报错，不按声明长度分配内存
+        let mut bytes = container_bytes();
+        let payload_len_offset = bytes.len() - 40 - 4;
+        bytes[payload_len_offset..payload_len_offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            LicenseContainer::parse(&bytes).unwrap_err(),
+            "授权文件被截断"
+        );
+    }
+
+    #[test]
+    fn recognizes_legacy_text_licenses() {
+        let legacy = b"c2VjcmV0:bm9uY2U=";
+        assert!(is_legacy_license(legacy));
+        assert_eq!(
+            LicenseContainer::parse(legacy).unwrap_err(),
+            LEGACY_REISSUE_MESSAGE
+        );
+        assert_eq!(
+            LicenseContainer::parse(b"not a license").unwrap_err(),
+            "授权文件格式错误 (缺少文件头)"
+        );
+        let parsed = LegacyLicense::parse(legacy).unwrap();
+        assert_eq!(parsed.ciphertext, b"secret");
+        assert_eq!(parsed.nonce, b"nonce");
+    }
+}
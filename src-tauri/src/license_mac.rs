@@ -0,0 +1,349 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::entitlements::{Edition, FeatureValue};
+use crate::license_data::AuthorizationData;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// MAC 密钥由授权密钥派生，而授权密钥随客户端下发，持有客户端即可重新计算 MAC，因此 MAC 不是安全边界：
+// 授权内容的完整性完全依赖 Ed25519 签名，MAC 只用于发现签发流程中的字段编码错误。
+// 新版校验值带算法前缀，旧版为无前缀的 SHA-256 十六进制串
+const MAC_PREFIX: &str = "hmac-sha256:";
+const MAC_DOMAIN: &[u8] = b"authorization-license-mac-v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumKind {
+    HmacSha256,
+    LegacySha256, // 只出现在显式启用的旧版授权校验中
+}
+
+// 对授权数据（除 checksum 外的全部字段）计算带密钥的 MAC
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn compute_mac(auth_data: &AuthorizationData, mac_key: &[u8; 32]) -> String {
+    let mac = mac_over(auth_data, mac_key);
+    format!("{}{}", MAC_PREFIX, hex::encode(mac.finalize().into_bytes()))
+}
+
+// 校验授权数据的 MAC；旧版无前缀校验和只能经由 verify_legacy_checksum 校验
+pub(crate) fn verify_checksum(
+    auth_data: &AuthorizationData,
+    mac_key: &[u8; 32],
+) -> Result<(), String> {
+    let mac_hex = auth_data
+        .checksum
+        .strip_prefix(MAC_PREFIX)
+        .ok_or_else(|| "授权文件缺少 MAC".to_string())?;
+    let expected = hex::decode(mac_hex).map_err(|_| "授权文件 MAC 编码无效".to_string())?;
+    mac_over(auth_data, mac_key)
+        .verify_slice(&expected)
+        .map_err(|_| "授权文件 MAC 不匹配，可能被篡改".to_string())
+}
+
+// 校验旧版无密钥校验和；旧版校验和只覆盖设备码、序列号和签发时间，不能用于带新字段的授权
+pub(crate) fn verify_legacy_checksum(auth_data: &AuthorizationData) -> Result<(), String> {
+    let has_new_fields = auth_data.not_before.is_some()
+        || auth_data.expires_at.is_some()
+        || auth_data.edition != Edition::default()
+        || !auth_data.features.is_empty()
+        || auth_data.fingerprint.is_some()
+        || auth_data.request_nonce.is_some()
+        || auth_data.product_id.is_some();
+    if has_new_fields {
+        return Err(
+            "授权文件包含有效期、功能、指纹、申请编号或产品标识字段，但只有旧版校验和".to_string(),
+        );
+    }
+    let expected = legacy_checksum(
+        &auth_data.device_code,
+        &auth_data.serial_number,
+        &auth_data.issued_at,
+    );
+    if auth_data.checksum != expected {
+        return Err("授权文件校验和不匹配，可能被篡改".to_string());
+    }
+    Ok(())
+}
+
+// 旧版校验和：字段直接拼接后做 SHA-256，仅用于兼容已部署的授权
+pub(crate) fn legacy_checksum(
+    device_code: &str,
+    serial_number: &str,
+    issued_at: &DateTime<Utc>,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}{}{}", device_code, serial_number, issued_at).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// 规范编码：每个字段按固定顺序写入，字符串带 4 字节长度前缀，可选字段带存在标记
+fn mac_over(auth_data: &AuthorizationData, mac_key: &[u8; 32]) -> HmacSha256 {
+    let mut mac =
+        <HmacSha256 as KeyInit>::new_from_slice(mac_key).expect("HMAC accepts keys of any length");
+    let mut encoder = CanonicalEncoder { mac: &mut mac };
+    encoder.bytes(MAC_DOMAIN);
+    encoder.str(&auth_data.device_code);
+    encoder.time(&auth_data.issued_at);
+    encoder.optional_time(auth_data.not_before.as_ref());
+    encoder.optional_time(auth_data.expires_at.as_ref());
+    encoder.str(edition_name(auth_data.edition));
+    encoder.u64(auth_data.features.len() as u64);
+    for (name, value) in &auth_data.features {
+        encoder.str(name);
+        match value {
+            FeatureValue::Flag(enabled) => {
+                encoder.tag(b'f');
+                encoder.tag(u8::from(*enabled));
+            }
+            FeatureValue::Limit(limit) => {
+                encoder.tag(b'l');
+                encoder.u64(*limit);
+            }
+        }
+    }
+    encoder.str(&auth_data.serial_number);
+    // 指纹字段追加在末尾且只在存在时编码，不影响此前签发的授权的 MAC
+    if let Some(fingerprint) = &auth_data.fingerprint {
+        encoder.tag(b'p');
+        encoder.tag(fingerprint.threshold_percent);
+        encoder.u64(fingerprint.components.len() as u64);
+        for component in &fingerprint.components {
+            encoder.str(&component.name);
+            encoder.u64(u64::from(component.weight));
+            encoder.str(&component.digest);
+        }
+    }
+    if let Some(nonce) = &auth_data.request_nonce {
+        encoder.tag(b'n');
+        encoder.str(nonce);
+    }
+    if let Some(product_id) = &auth_data.product_id {
+        encoder.tag(b'i');
+        encoder.str(product_id);
+    }
+    mac
+}
+
+fn edition_name(edition: Edition) -> &'static str {
+    match edition {
+        Edition::Trial => "trial",
+        Edition::Standard => "standard",
+        Edition::Professional => "professional",
+        Edition::Enterprise => "enterprise",
+    }
+}
+
+struct CanonicalEncoder<'a> {
+    mac: &'a mut HmacSha256,
+}
+
+impl CanonicalEncoder<'_> {
+    fn tag(&mut self, tag: u8) {
+        self.mac.update(&[tag]);
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.mac.update(&value.to_be_bytes());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.mac.update(&(value.len() as u32).to_be_bytes());
+        self.mac.update(value);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    fn time(&mut self, value: &DateTime<Utc>) {
+        self.str(&value.to_rfc3339_opts(SecondsFormat::Nanos, true));
+    }
+
+    fn optional_time(&mut self, value: Option<&DateTime<Utc>>) {
+        match value {
+            Some(time) => {
+                self.tag(1);
+                self.time(time);
+            }
+            None => self.tag(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    use crate::entitlements::FeatureSet;
+    use crate::fingerprint::{DeviceFingerprint, FingerprintComponent};
+
+    const MAC_KEY: [u8; 32] = [5; 32];
+
+    type Change = fn(&mut AuthorizationData);
+
+    fn auth_data() -> AuthorizationData {
+        AuthorizationData {
+            device_code: "fb95dce2cc77799c".to_string(),
+            issued_at: Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap(),
+            not_before: None,
+            expires_at: None,
+            edition: Edition::default(),
+            features: FeatureSet::new(),
+            fingerprint: None,
+            request_nonce: None,
+            product_id: None,
+            serial_number: "3f0c5a9e-0000-4000-8000-000000000001".to_string(),
+            checksum: String::new(),
+        }
+    }
+
+    fn with_mac(mut data: AuthorizationData) -> AuthorizationData {
+        data.checksum = compute_mac(&data, &MAC_KEY);
+        data
+    }
+
+    #[test]
+    fn mac_round_trips() {
+        let data = with_mac(auth_data());
+        assert!(data.checksum.starts_with(MAC_PREFIX));
+        assert_eq!(verify_checksum(&data, &MAC_KEY), Ok(()));
+        assert!(verify_checksum(&data, &[6; 32]).is_err());
+    }
+
+    #[test]
+    fn changing_any_field_changes_the_mac() {
+        let original = with_mac(auth_data());
+        let changes: Vec<(&str, Change)> = vec![
+            ("device_code", |d| {
+                d.device_code = "0123456789abcdef".to_string()
+            }),
+            ("issued_at", |d| d.issued_at += Duration::seconds(1)),
+            ("not_before", |d| d.not_before = Some(d.issued_at)),
+            ("expires_at", |d| {
+                d.expires_at = Some(d.issued_at + Duration::days(30))
+            }),
+            ("edition", |d| d.edition = Edition::Enterprise),
+            ("feature flag", |d| {
+                d.features
+                    .insert("multiplayer".to_string(), FeatureValue::Flag(true));
+            }),
+            ("feature limit", |d| {
+                d.features
+                    .insert("multiplayer".to_string(), FeatureValue::Limit(1));
+            }),
+            ("fingerprint", |d| {
+                d.fingerprint = Some(DeviceFingerprint {
+                    threshold_percent: 60,
+                    components: vec![FingerprintComponent::new("machine_id", 3, "abc")],
+                })
+            }),
+            ("request_nonce", |d| {
+                d.request_nonce = Some("00112233445566778899aabbccddeeff".to_string())
+            }),
+            ("product_id", |d| {
+                d.product_id = Some("bupt-vr-client".to_string())
+            }),
+            ("serial_number", |d| d.serial_number.push('2')),
+        ];
+        for (field, change) in changes {
+            let mut tampered = original.clone();
+            change(&mut tampered);
+            assert!(
+                verify_checksum(&tampered, &MAC_KEY).is_err(),
+                "修改 {} 后 MAC 仍然有效",
+                field
+            );
+            assert_ne!(
+                compute_mac(&tampered, &MAC_KEY),
+                original.checksum,
+                "{}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn field_boundaries_do_not_collide() {
+        // 旧版校验和直接拼接字段，移动字段边界得到相同的结果
+        let mut moved = auth_data();
+        moved.device_code = "fb95dce2cc77799c3".to_string();
+        moved.serial_number = "f0c5a9e-0000-4000-8000-000000000001".to_string();
+        let original = auth_data();
+        assert_eq!(
+            legacy_checksum(
+                &original.device_code,
+                &original.serial_number,
+                &original.issued_at
+            ),
+            legacy_checksum(&moved.device_code, &moved.serial_number, &moved.issued_at)
+        );
+        assert_ne!(
+            compute_mac(&original, &MAC_KEY),
+            compute_mac(&moved, &MAC_KEY)
+        );
+
+        // 功能名与取值的边界同样不能挪动
+        let mut a = auth_data();
+        a.features.insert("ab".to_string(), FeatureValue::Limit(1));
+        let mut b = auth_data();
+        b.features.insert("a".to_string(), FeatureValue::Limit(1));
+        assert_ne!(compute_mac(&a, &MAC_KEY), compute_mac(&b, &MAC_KEY));
+
+        // 开关与数量上限按类型区分：Flag(true) 与 Limit(1) 不相同
+        let mut flag = auth_data();
+        flag.features
+            .insert("multiplayer".to_string(), FeatureValue::Flag(true));
+        let mut limit = auth_data();
+        limit
+            .features
+            .insert("multiplayer".to_string(), FeatureValue::Limit(1));
+        assert_ne!(compute_mac(&flag, &MAC_KEY), compute_mac(&limit, &MAC_KEY));
+    }
+
+    #[test]
+    fn legacy_checksum_only_covers_legacy_licenses() {
+        let mut legacy = auth_data();
+        legacy.checksum = legacy_checksum(
+            &legacy.device_code,
+            &legacy.serial_number,
+            &legacy.issued_at,
+        );
+        assert_eq!(verify_legacy_checksum(&legacy), Ok(()));
+        // 新版校验流程不接受旧版校验和
+        assert_eq!(
+            verify_checksum(&legacy, &MAC_KEY).unwrap_err(),
+            "授权文件缺少 MAC"
+        );
+
+        let mut tampered = legacy.clone();
+        tampered.serial_number.push('2');
+        assert!(verify_legacy_checksum(&tampered).is_err());
+
+        // 带新字段的授权不能用旧版校验和绕过 MAC
+        let new_fields: Vec<Change> = vec![
+            |d| d.not_before = Some(d.issued_at),
+            |d| d.expires_at = Some(d.issued_at + Duration::days(30)),
+            |d| d.edition = Edition::Professional,
+            |d| {
+                d.features
+                    .insert("recording".to_string(), FeatureValue::Flag(true));
+            },
+            |d| {
+                d.fingerprint = Some(DeviceFingerprint {
+                    threshold_percent: 60,
+                    components: Vec::new(),
+                })
+            },
+            |d| d.request_nonce = Some("00112233445566778899aabbccddeeff".to_string()),
+            |d| d.product_id = Some("bupt-vr-client".to_string()),
+        ];
+        for add_field in new_fields {
+            let mut data = legacy.clone();
+            add_field(&mut data);
+            let error = verify_legacy_checksum(&data).unwrap_err();
+            assert!(error.contains("旧版校验和"), "{}", error);
+        }
+    }
+}
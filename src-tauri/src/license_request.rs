@@ -0,0 +1,215 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::device_code::DeviceCode;
+use crate::fingerprint::{FingerprintComponent, KNOWN_COMPONENTS};
+use crate::get_device_code;
+use crate::kdf::random_bytes;
+
+pub const LICENSE_REQUEST_FILE_NAME: &str = "license_request.req";
+
+const REQUEST_FORMAT: &str = "authorization-license-request";
+const REQUEST_FORMAT_VERSION: u8 = 2;
+const NONCE_LEN: usize = 16;
+const MAX_FIELD_LEN: usize = 128;
+
+// 客户端生成的授权申请：授权端据此签发，nonce 原样写入授权，用于一一对应申请与授权
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LicenseRequest {
+    pub device_code: DeviceCode,
+    pub product_id: String,
+    pub client_version: String,
+    #[serde(default)]
+    pub hardware: Vec<FingerprintComponent>, // 组件摘要，不含原始序列号
+    pub nonce: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_reference: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// 申请文件：payload 为 LicenseRequest 的 JSON 原文，checksum 为 payload 的 SHA-256 十六进制串。
+// 客户端没有可信的签名密钥，checksum 只能发现传输中的损坏，任何人都能改动内容后重新计算，不能证明申请来源
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LicenseRequestFile {
+    format: String,
+    version: u8,
+    payload: String,
+    checksum: String,
+}
+
+impl LicenseRequest {
+    // 读取本机设备码与硬件指纹，生成新的授权申请
+    pub fn create(
+        product_id: &str,
+        client_version: &str,
+        customer_reference: Option<&str>,
+    ) -> Result<Self, String> {
+        let device = get_device_code::local_device()?;
+        let request = Self {
+            device_code: DeviceCode::parse(&device.device_code)?,
+            product_id: product_id.trim().to_string(),
+            client_version: client_version.trim().to_string(),
+            hardware: device.fingerprint.components,
+            nonce: hex::encode(random_bytes::<NONCE_LEN>()?),
+            customer_reference: customer_reference
+                .map(str::trim)
+                .filter(|reference| !reference.is_empty())
+                .map(str::to_string),
+            created_at: Utc::now(),
+        };
+        request.validate()?;
+        Ok(request)
+    }
+
+    // 附加完整性校验和并编码为申请文件内容
+    pub fn to_file_bytes(&self) -> Result<Vec<u8>, String> {
+        let payload = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let file = LicenseRequestFile {
+            format: REQUEST_FORMAT.to_string(),
+            version: REQUEST_FORMAT_VERSION,
+            checksum: payload_checksum(&payload),
+            payload,
+        };
+        serde_json::to_vec_pretty(&file).map_err(|e| e.to_string())
+    }
+
+    // 解析申请文件：检查格式、完整性校验和与各字段；申请来源需由操作员另行核实
+    pub fn from_file_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let file: LicenseRequestFile =
+            serde_json::from_slice(bytes).map_err(|e| format!("授权申请文件格式错误: {}", e))?;
+        if file.format != REQUEST_FORMAT {
+            return Err(format!("不是授权申请文件 (格式: {})", file.format));
+        }
+        if file.version != REQUEST_FORMAT_VERSION {
+            return Err(format!(
+                "不支持的授权申请文件版本: {} (支持 {})",
+                file.version, REQUEST_FORMAT_VERSION
+            ));
+        }
+        if !file
+            .checksum
+            .eq_ignore_ascii_case(&payload_checksum(&file.payload))
+        {
+            return Err("授权申请文件完整性校验失败，文件可能已损坏".to_string());
+        }
+
+        let request: Self = serde_json::from_str(&file.payload)
+            .map_err(|e| format!("授权申请内容解析错误: {}", e))?;
+        request.validate()?;
+        Ok(request)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("产品标识", self.product_id.as_str()),
+            ("客户端版本", self.client_version.as_str()),
+        ] {
+            if value.trim().is_empty() {
+                return Err(format!("授权申请缺少{}", name));
+            }
+            if value.len() > MAX_FIELD_LEN {
+                return Err(format!("授权申请的{}过长", name));
+            }
+        }
+        if let Some(reference) = &self.customer_reference {
+            if reference.len() > MAX_FIELD_LEN {
+                return Err("授权申请的客户备注过长".to_string());
+            }
+        }
+        // 硬件组件会原样写入签名的授权，只接受各平台实际采集的组件
+        if self.hardware.len() > KNOWN_COMPONENTS.len() {
+            return Err(format!(
+                "授权申请的硬件组件过多 ({} 个)",
+                self.hardware.len()
+            ));
+        }
+        for (index, component) in self.hardware.iter().enumerate() {
+            component.validate()?;
+            if self.hardware[..index]
+                .iter()
+                .any(|other| other.name == component.name)
+            {
+                return Err(format!("授权申请的硬件组件重复: {}", component.name));
+            }
+        }
+        if self.nonce.len() != NONCE_LEN * 2 || !self.nonce.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("授权申请编号无效: {}", self.nonce));
+        }
+        // 只拒绝明显来自未来的申请，允许客户端时钟有少量偏差
+        if self.created_at > Utc::now() + Duration::days(1) {
+            return Err(format!(
+                "授权申请的创建时间 ({}) 晚于当前时间",
+                self.created_at
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn payload_checksum(payload: &str) -> String {
+    hex::encode(Sha256::digest(payload.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> LicenseRequest {
+        LicenseRequest {
+            device_code: DeviceCode::parse("fb95dce2cc77799c").unwrap(),
+            product_id: "bupt-vr-client".to_string(),
+            client_version: "1.0.0".to_string(),
+            hardware: vec![
+                FingerprintComponent::new("machine_id", 3, "machine"),
+                FingerprintComponent::new("mac_address", 1, "00:11:22:33:44:55"),
+            ],
+            nonce: "00112233445566778899aabbccddeeff".to_string(),
+            customer_reference: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn request_files_round_trip() {
+        let bytes = request().to_file_bytes().unwrap();
+        let parsed = LicenseRequest::from_file_bytes(&bytes).unwrap();
+        assert_eq!(parsed.nonce, request().nonce);
+        assert_eq!(parsed.hardware, request().hardware);
+    }
+
+    #[test]
+    fn detects_corrupted_payloads() {
+        let bytes = request().to_file_bytes().unwrap();
+        let mut file: LicenseRequestFile = serde_json::from_slice(&bytes).unwrap();
+        file.payload = file.payload.replace("1.0.0", "1.0.1");
+        let error =
+            LicenseRequest::from_file_bytes(&serde_json::to_vec(&file).unwrap()).unwrap_err();
+        assert!(error.contains("完整性校验失败"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_untrusted_hardware_components() {
+        type Change = fn(&mut Vec<FingerprintComponent>);
+        let changes: Vec<(&str, Change)> = vec![
+            ("未知的硬件组件", |h| {
+                h[0].name = "cpu_serial".to_string()
+            }),
+            ("超出范围", |h| h[0].weight = 0),
+            ("超出范围", |h| h[0].weight = u32::MAX),
+            ("摘要无效", |h| h[0].digest = "not-a-digest".to_string()),
+            ("摘要无效", |h| h[0].digest = h[0].digest.to_uppercase()),
+            ("重复", |h| h[1] = h[0].clone()),
+            ("过多", |h| {
+                let extra = h[1].clone();
+                h.extend(std::iter::repeat_n(extra, KNOWN_COMPONENTS.len()));
+            }),
+        ];
+        for (expected, change) in changes {
+            let mut request = request();
+            change(&mut request.hardware);
+            let error = request.validate().unwrap_err();
+            assert!(error.contains(expected), "{}: {}", expected, error);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+#[cfg(any(test, feature = "app"))]
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+
+// 授权文件解密后的内容：payload 为 AuthorizationData 的 JSON 原文，signature 为其 Ed25519 签名
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignedLicense {
+    pub key_id: String,
+    pub payload: String,
+    pub signature: String,
+}
+
+// 签名函数（仅授权端持有私钥）
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn sign_payload(payload: &str, key_id: &str, signing_key: &SigningKey) -> SignedLicense {
+    let signature = signing_key.sign(payload.as_bytes());
+    SignedLicense {
+        key_id: key_id.to_string(),
+        payload: payload.to_string(),
+        signature: STANDARD.encode(signature.to_bytes()),
+    }
+}
+
+// 验签函数（校验端只需要公钥）
+pub(crate) fn verify_signed_license(
+    signed: &SignedLicense,
+    verifying_key: &VerifyingKey,
+) -> Result<(), String> {
+    let signature_bytes = STANDARD
+        .decode(&signed.signature)
+        .map_err(|e| format!("签名编码无效: {}", e))?;
+    let signature =
+        Signature::from_slice(&signature_bytes).map_err(|e| format!("签名长度无效: {}", e))?;
+    verifying_key
+        .verify(signed.payload.as_bytes(), &signature)
+        .map_err(|_| "授权文件签名校验失败，可能被篡改或非本授权端签发".to_string())
+}
@@ -0,0 +1,193 @@
+// 内存中的模拟 ADB 后端，供测试使用；只在测试或启用 testing 特性时编译
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::adb_backend::AdbBackend;
+use crate::adb_client::{parse_devices_long, AdbDevice, TransferProgress};
+
+// 模拟设备：设备信息取自一行 adb devices -l 输出，文件系统保存在内存中
+#[derive(Debug, Clone)]
+pub struct MockDevice {
+    pub info: AdbDevice,
+    pub files: HashMap<String, Vec<u8>>,
+    pub shell_outputs: HashMap<String, String>,
+    pub read_only_dirs: Vec<String>, // 推送到这些目录下会失败，模拟没有写权限
+}
+
+impl MockDevice {
+    // line 为 adb devices -l 中的一行，如 "SERIAL device model:Quest_3 transport_id:1"
+    pub fn from_listing(line: &str) -> Self {
+        let info = parse_devices_long(line)
+            .into_iter()
+            .next()
+            .expect("模拟设备需要一行有效的 adb devices -l 输出");
+        Self {
+            info,
+            files: HashMap::new(),
+            shell_outputs: HashMap::new(),
+            read_only_dirs: Vec::new(),
+        }
+    }
+
+    pub fn with_file(mut self, path: &str, content: &[u8]) -> Self {
+        self.files.insert(path.to_string(), content.to_vec());
+        self
+    }
+
+    pub fn with_shell_output(mut self, command: &str, output: &str) -> Self {
+        self.shell_outputs
+            .insert(command.to_string(), output.to_string());
+        self
+    }
+
+    pub fn with_read_only_dir(mut self, dir: &str) -> Self {
+        self.read_only_dirs.push(dir.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MockAdbBackend {
+    devices: Mutex<Vec<MockDevice>>,
+}
+
+impl MockAdbBackend {
+    pub fn new(devices: Vec<MockDevice>) -> Self {
+        Self {
+            devices: Mutex::new(devices),
+        }
+    }
+
+    pub fn add_device(&self, device: MockDevice) {
+        self.devices.lock().unwrap().push(device);
+    }
+
+    pub fn remove_device(&self, serial: &str) {
+        self.devices
+            .lock()
+            .unwrap()
+            .retain(|device| device.info.serial != serial);
+    }
+
+    pub fn file(&self, serial: &str, path: &str) -> Option<Vec<u8>> {
+        self.with_device(serial, |device| Ok(device.files.get(path).cloned()))
+            .ok()
+            .flatten()
+    }
+
+    // 设备上全部文件的路径，按字典序
+    pub fn file_paths(&self, serial: &str) -> Vec<String> {
+        let mut paths = self
+            .with_device(serial, |device| Ok(device.files.keys().cloned().collect()))
+            .unwrap_or_else(|_| Vec::new());
+        paths.sort();
+        paths
+    }
+
+    pub fn put_file(&self, serial: &str, path: &str, content: &[u8]) {
+        let _ = self.with_device(serial, |device| {
+            device.files.insert(path.to_string(), content.to_vec());
+            Ok(())
+        });
+    }
+
+    // 与真实设备一致：找不到设备或设备未就绪时报错
+    fn with_device<T>(
+        &self,
+        serial: &str,
+        f: impl FnOnce(&mut MockDevice) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices
+            .iter_mut()
+            .find(|device| device.info.serial == serial)
+            .ok_or_else(|| format!("device '{}' not found", serial))?;
+        if !device.info.is_ready() {
+            return Err(format!("device '{}' is {}", serial, device.info.state_text));
+        }
+        f(device)
+    }
+}
+
+impl AdbBackend for MockAdbBackend {
+    fn devices(&self) -> Result<Vec<AdbDevice>, String> {
+        Ok(self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|device| device.info.clone())
+            .collect())
+    }
+
+    fn pull(
+        &self,
+        serial: &str,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<Vec<u8>, String> {
+        let content = self.with_device(serial, |device| {
+            device
+                .files
+                .get(remote_path)
+                .cloned()
+                .ok_or_else(|| format!("远端文件不存在: {}", remote_path))
+        })?;
+        let total = content.len() as u64;
+        progress(TransferProgress {
+            transferred: total,
+            total: Some(total),
+        });
+        Ok(content)
+    }
+
+    fn push(
+        &self,
+        serial: &str,
+        data: &[u8],
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<(), String> {
+        self.with_device(serial, |device| {
+            if device
+                .read_only_dirs
+                .iter()
+                .any(|dir| remote_path.starts_with(dir.as_str()))
+            {
+                return Err(format!(
+                    "推送到 {} 失败: Read-only file system",
+                    remote_path
+                ));
+            }
+            device.files.insert(remote_path.to_string(), data.to_vec());
+            Ok(())
+        })?;
+        let total = data.len() as u64;
+        progress(TransferProgress {
+            transferred: total,
+            total: Some(total),
+        });
+        Ok(())
+    }
+
+    fn shell(&self, serial: &str, command: &str) -> Result<String, String> {
+        self.with_device(serial, |device| {
+            Ok(device
+                .shell_outputs
+                .get(command)
+                .cloned()
+                .unwrap_or_default())
+        })
+    }
+
+    fn rename(&self, serial: &str, from: &str, to: &str) -> Result<(), String> {
+        self.with_device(serial, |device| {
+            let content = device
+                .files
+                .remove(from)
+                .ok_or_else(|| format!("mv: {}: No such file or directory", from))?;
+            device.files.insert(to.to_string(), content);
+            Ok(())
+        })
+    }
+}
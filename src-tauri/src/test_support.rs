@@ -0,0 +1,73 @@
+// 单元测试共用的签发流程：固定的授权密钥与签名密钥，按授权端相同的步骤生成授权文件
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+
+use crate::en_de_crypt::encrypt;
+use crate::entitlements::{Edition, FeatureValue};
+use crate::kdf::{self, KdfAlgorithm};
+use crate::license_data::AuthorizationData;
+use crate::license_format::LicenseContainer;
+use crate::license_mac::compute_mac;
+use crate::license_sign::sign_payload;
+use crate::verifier_keys::{VerifierKey, VerifierKeySet};
+use crate::verify::DEFAULT_PRODUCT_ID;
+
+pub(crate) const DEVICE_CODE: &str = "fb95dce2cc77799c";
+pub(crate) const OTHER_DEVICE_CODE: &str = "0123456789abcdef";
+pub(crate) const KEY_ID: &str = "20240101-test";
+pub(crate) const LICENSE_KEY: [u8; 32] = [7; 32];
+
+pub(crate) fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[9; 32])
+}
+
+pub(crate) fn verifier_keys() -> VerifierKeySet {
+    VerifierKeySet {
+        license_key: hex::encode(LICENSE_KEY),
+        keys: vec![VerifierKey {
+            key_id: KEY_ID.to_string(),
+            public_key: hex::encode(signing_key().verifying_key().to_bytes()),
+        }],
+    }
+}
+
+pub(crate) fn auth_data() -> AuthorizationData {
+    AuthorizationData {
+        device_code: DEVICE_CODE.to_string(),
+        issued_at: Utc::now(),
+        not_before: None,
+        expires_at: None,
+        edition: Edition::Professional,
+        features: [
+            ("multiplayer".to_string(), FeatureValue::Flag(true)),
+            ("max_sessions".to_string(), FeatureValue::Limit(8)),
+        ]
+        .into_iter()
+        .collect(),
+        fingerprint: None,
+        request_nonce: Some("00112233445566778899aabbccddeeff".to_string()),
+        product_id: Some(DEFAULT_PRODUCT_ID.to_string()),
+        serial_number: "serial-1".to_string(),
+        checksum: String::new(),
+    }
+}
+
+// 与授权端相同的签发流程：MAC -> 签名 -> 加密 (附加数据绑定设备码与产品)
+pub(crate) fn seal(mut data: AuthorizationData, signing_key: &SigningKey) -> Vec<u8> {
+    data.checksum = compute_mac(&data, &kdf::derive_mac_key(&LICENSE_KEY).unwrap());
+    let signed = sign_payload(&serde_json::to_string(&data).unwrap(), KEY_ID, signing_key);
+    let salt = kdf::random_salt().unwrap();
+    let key =
+        kdf::derive_license_encryption_key(KdfAlgorithm::HkdfSha256, &LICENSE_KEY, &salt).unwrap();
+    let mut container = LicenseContainer::new(KEY_ID, salt.to_vec());
+    let aad = container.associated_data(&data.device_code, DEFAULT_PRODUCT_ID);
+    let (payload, nonce) = encrypt(
+        serde_json::to_string(&signed).unwrap().as_bytes(),
+        &key,
+        &aad,
+    )
+    .unwrap();
+    container.nonce = nonce;
+    container.payload = payload;
+    container.to_bytes().unwrap()
+}
@@ -0,0 +1,40 @@
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+// 校验端所需的公开密钥
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VerifierKey {
+    pub key_id: String,
+    pub public_key: String,
+}
+
+// 导出给客户端的校验密钥集合：按 key_id 选择公钥，新旧授权可同时校验
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VerifierKeySet {
+    pub license_key: String,
+    pub keys: Vec<VerifierKey>,
+}
+
+impl VerifierKeySet {
+    // 解析授权端导出的 verifier_keys.json
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("校验密钥格式错误: {}", e))
+    }
+
+    pub(crate) fn license_key_bytes(&self) -> Result<Vec<u8>, String> {
+        hex::decode(&self.license_key).map_err(|e| format!("授权密钥解码失败: {}", e))
+    }
+
+    pub(crate) fn verifying_key(&self, key_id: &str) -> Result<VerifyingKey, String> {
+        let entry = self
+            .keys
+            .iter()
+            .find(|k| k.key_id == key_id)
+            .ok_or_else(|| format!("未知的签名密钥 ID: {}", key_id))?;
+        let bytes: [u8; 32] = hex::decode(&entry.public_key)
+            .map_err(|e| format!("签名公钥 {} 解码失败: {}", key_id, e))?
+            .try_into()
+            .map_err(|_| format!("签名公钥 {} 长度错误", key_id))?;
+        VerifyingKey::from_bytes(&bytes).map_err(|e| format!("签名公钥 {} 无效: {}", key_id, e))
+    }
+}
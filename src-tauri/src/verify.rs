@@ -0,0 +1,419 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::device_code::DeviceCode;
+use crate::en_de_crypt::{decrypt, CryptoError};
+use crate::fingerprint::{DeviceFingerprint, FingerprintMatch};
+use crate::get_device_code::local_fingerprint;
+use crate::kdf;
+use crate::license_data::AuthorizationData;
+use crate::license_format::{
+    is_legacy_license, LegacyLicense, LicenseContainer, LEGACY_FORMAT_VERSION, LEGACY_LICENSE_KEY,
+    LEGACY_REISSUE_MESSAGE,
+};
+use crate::license_mac::{verify_checksum, verify_legacy_checksum, ChecksumKind};
+use crate::license_sign::{verify_signed_license, SignedLicense};
+use crate::verifier_keys::VerifierKeySet;
+use crate::verify_error::{VerifyError, VerifyErrorKind};
+
+pub const DEFAULT_CLOCK_SKEW_SECS: i64 = 300; // 校验有效期时允许的时钟偏差
+pub const MAX_CLOCK_SKEW_SECS: i64 = 86_400; // 允许设置的最大时钟偏差 (1 天)
+pub const DEFAULT_PRODUCT_ID: &str = "bupt-vr-client"; // 未指定产品标识时绑定到授权文件的产品
+
+// 校验参数：授权文件绑定的产品标识、允许的时钟偏差和校验时刻；
+// live_fingerprint 为当前设备的硬件指纹，与授权中记录的指纹做容错比对；
+// 授权记录了指纹而未提供时读取本机硬件信息，只有不在客户端本机校验 (如签发端自检) 时才设置 skip_fingerprint；
+// allow_legacy 接受旧版固定密钥授权，其内容任何人都能伪造，只用于重新签发前的过渡期
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    pub product_id: String,
+    pub clock_skew: Duration,
+    pub now: DateTime<Utc>,
+    pub live_fingerprint: Option<DeviceFingerprint>,
+    pub skip_fingerprint: bool,
+    pub allow_legacy: bool,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            product_id: DEFAULT_PRODUCT_ID.to_string(),
+            clock_skew: Duration::seconds(DEFAULT_CLOCK_SKEW_SECS),
+            now: Utc::now(),
+            live_fingerprint: None,
+            skip_fingerprint: false,
+            allow_legacy: false,
+        }
+    }
+}
+
+// 校验通过的授权，附带容器头部信息，供调用方记录日志
+#[derive(Debug, Clone)]
+pub struct VerifiedLicense {
+    pub auth_data: AuthorizationData,
+    pub format_version: u8,
+    pub key_id: String,
+    pub checksum_kind: ChecksumKind,
+    pub fingerprint_match: Option<FingerprintMatch>, // 未比对指纹时为 None
+}
+
+// 用默认参数校验授权文件内容；device_code 为客户端 device_code.bin 中的设备码
+pub fn verify(
+    license_bytes: &[u8],
+    device_code: &str,
+    keys: &VerifierKeySet,
+) -> Result<AuthorizationData, VerifyError> {
+    verify_license(license_bytes, device_code, keys, &VerifyOptions::default())
+        .map(|verified| verified.auth_data)
+}
+
+// 完整校验流程：解析容器 -> 解密 (附加数据绑定设备码与产品) -> 验签 -> 设备码 -> 产品标识 -> MAC -> 有效期 -> 指纹
+pub fn verify_license(
+    license_bytes: &[u8],
+    device_code: &str,
+    keys: &VerifierKeySet,
+    options: &VerifyOptions,
+) -> Result<VerifiedLicense, VerifyError> {
+    let device_code = DeviceCode::parse(device_code)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::InvalidDeviceCode, e))?;
+    if is_legacy_license(license_bytes) {
+        if !options.allow_legacy {
+            return Err(VerifyError::new(
+                VerifyErrorKind::ReissueRequired,
+                LEGACY_REISSUE_MESSAGE,
+            ));
+        }
+        return verify_legacy_license(license_bytes, &device_code);
+    }
+    let key = keys
+        .license_key_bytes()
+        .map_err(|e| VerifyError::new(VerifyErrorKind::KeyUnavailable, e))?;
+    let container = LicenseContainer::parse(license_bytes)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::BadEncoding, e))?;
+
+    // 先确认本机认识头部的密钥 ID，此后的认证失败即视为篡改
+    keys.verifying_key(&container.key_id)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::WrongKey, e))?;
+    let encryption_key = kdf::derive_license_encryption_key(container.kdf, &key, &container.salt)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::KeyUnavailable, e))?;
+    // version 3 起附加数据包含设备码和产品标识，挪用到其他设备或产品目录会认证失败
+    let aad = container.associated_data(device_code.as_str(), &options.product_id);
+    let signed_json = decrypt(&container.payload, &container.nonce, &encryption_key, &aad)
+        .map_err(|e| match e {
+            CryptoError::AuthenticationFailed if !aad.is_empty() => VerifyError::new(
+                VerifyErrorKind::BindingMismatch,
+                format!(
+                    "授权文件与当前设备码或产品 ({}) 不匹配 (或已被篡改)",
+                    options.product_id
+                ),
+            ),
+            other => VerifyError::from(other),
+        })?;
+    let signed_license: SignedLicense = serde_json::from_str(&signed_json).map_err(|e| {
+        VerifyError::new(
+            VerifyErrorKind::BadEncoding,
+            format!("授权文件签名结构解析错误: {}", e),
+        )
+    })?;
+    if container.key_id != signed_license.key_id {
+        return Err(VerifyError::new(
+            VerifyErrorKind::Tampered,
+            format!(
+                "授权文件头部密钥 ID ({}) 与签名密钥 ID ({}) 不一致",
+                container.key_id, signed_license.key_id
+            ),
+        ));
+    }
+    let verifying_key = keys
+        .verifying_key(&signed_license.key_id)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::WrongKey, e))?;
+    verify_signed_license(&signed_license, &verifying_key)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::Tampered, e))?;
+    let auth_data: AuthorizationData =
+        serde_json::from_str(&signed_license.payload).map_err(|e| {
+            VerifyError::new(
+                VerifyErrorKind::BadEncoding,
+                format!("授权文件内容解析错误: {}", e),
+            )
+        })?;
+
+    check_device_code(&auth_data, &device_code)?;
+
+    // version 3 授权必须在签名内容中记录产品标识，不能只依赖附加数据
+    let product_id = auth_data.product_id.as_deref().ok_or_else(|| {
+        VerifyError::new(VerifyErrorKind::Tampered, "授权文件签名内容缺少产品标识")
+    })?;
+    if product_id != options.product_id {
+        return Err(VerifyError::new(
+            VerifyErrorKind::BindingMismatch,
+            format!(
+                "授权文件的产品标识 ({}) 与当前产品 ({}) 不匹配",
+                product_id, options.product_id
+            ),
+        ));
+    }
+
+    let mac_key = kdf::derive_mac_key(&key)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::KeyUnavailable, e))?;
+    verify_checksum(&auth_data, &mac_key)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::Tampered, e))?;
+
+    check_validity_window(&auth_data, options.now, options.clock_skew)?;
+
+    let fingerprint_match = match (&auth_data.fingerprint, &options.live_fingerprint) {
+        (Some(expected), Some(live)) => Some(expected.check(live)?),
+        // 只有授权记录了指纹时才读取本机硬件信息 (Windows 上需要调用 WMI)
+        (Some(expected), None) if !options.skip_fingerprint => {
+            let live = local_fingerprint().map_err(|e| {
+                VerifyError::new(
+                    VerifyErrorKind::FingerprintMismatch,
+                    format!("授权文件绑定了硬件指纹，但读取本机硬件信息失败: {}", e),
+                )
+            })?;
+            Some(expected.check(&live)?)
+        }
+        _ => None,
+    };
+
+    Ok(VerifiedLicense {
+        auth_data,
+        format_version: container.version,
+        key_id: container.key_id,
+        checksum_kind: ChecksumKind::HmacSha256,
+        fingerprint_match,
+    })
+}
+
+// 旧版授权：固定密钥解密，校验设备码和旧版校验和；没有签名、产品绑定和有效期
+fn verify_legacy_license(
+    license_bytes: &[u8],
+    device_code: &DeviceCode,
+) -> Result<VerifiedLicense, VerifyError> {
+    let legacy = LegacyLicense::parse(license_bytes)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::BadEncoding, e))?;
+    let json = decrypt(&legacy.ciphertext, &legacy.nonce, &LEGACY_LICENSE_KEY, &[])?;
+    let auth_data: AuthorizationData = serde_json::from_str(&json).map_err(|e| {
+        VerifyError::new(
+            VerifyErrorKind::BadEncoding,
+            format!("授权文件内容解析错误: {}", e),
+        )
+    })?;
+    check_device_code(&auth_data, device_code)?;
+    verify_legacy_checksum(&auth_data)
+        .map_err(|e| VerifyError::new(VerifyErrorKind::Tampered, e))?;
+
+    Ok(VerifiedLicense {
+        auth_data,
+        format_version: LEGACY_FORMAT_VERSION,
+        key_id: String::new(),
+        checksum_kind: ChecksumKind::LegacySha256,
+        fingerprint_match: None,
+    })
+}
+
+fn check_device_code(
+    auth_data: &AuthorizationData,
+    device_code: &DeviceCode,
+) -> Result<(), VerifyError> {
+    if DeviceCode::parse(&auth_data.device_code).as_ref() != Ok(device_code) {
+        return Err(VerifyError::new(
+            VerifyErrorKind::DeviceMismatch,
+            format!(
+                "设备码不匹配 (授权文件: {}, 设备文件: {})",
+                auth_data.device_code, device_code
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// 把调用方传入的时钟偏差秒数转换为 Duration，只接受 0..=MAX_CLOCK_SKEW_SECS
+pub fn clock_skew_from_secs(secs: i64) -> Result<Duration, String> {
+    if !(0..=MAX_CLOCK_SKEW_SECS).contains(&secs) {
+        return Err(format!(
+            "时钟偏差必须在 0 到 {} 秒之间: {}",
+            MAX_CLOCK_SKEW_SECS, secs
+        ));
+    }
+    Ok(Duration::seconds(secs))
+}
+
+// 检查授权是否处于有效期内，允许 clock_skew 的时钟偏差；超出时间范围时按最早/最晚时刻处理
+pub(crate) fn check_validity_window(
+    auth_data: &AuthorizationData,
+    now: DateTime<Utc>,
+    clock_skew: Duration,
+) -> Result<(), VerifyError> {
+    let latest = now
+        .checked_add_signed(clock_skew)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let earliest = now
+        .checked_sub_signed(clock_skew)
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+    if let Some(not_before) = auth_data.not_before {
+        if latest < not_before {
+            return Err(VerifyError::new(
+                VerifyErrorKind::NotYetValid,
+                format!("授权尚未生效 (生效时间: {})", not_before),
+            ));
+        }
+    }
+    if let Some(expires_at) = auth_data.expires_at {
+        if earliest >= expires_at {
+            return Err(VerifyError::new(
+                VerifyErrorKind::Expired,
+                format!("授权已过期 (到期时间: {})", expires_at),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    use crate::en_de_crypt::encrypt;
+    use crate::entitlements::{Edition, FeatureSet};
+    use crate::fingerprint::FingerprintComponent;
+    use crate::license_mac::legacy_checksum;
+    use crate::test_support::{
+        auth_data, seal, signing_key, verifier_keys, DEVICE_CODE, OTHER_DEVICE_CODE,
+    };
+
+    // 按旧版流程生成 "base64(密文):base64(nonce)" 授权
+    fn legacy_license(edit: fn(&mut AuthorizationData)) -> Vec<u8> {
+        let issued_at = Utc::now();
+        let serial_number = "3f0c5a9e-0000-4000-8000-000000000001".to_string();
+        let mut data = AuthorizationData {
+            device_code: DEVICE_CODE.to_string(),
+            issued_at,
+            not_before: None,
+            expires_at: None,
+            edition: Edition::default(),
+            features: FeatureSet::new(),
+            fingerprint: None,
+            request_nonce: None,
+            product_id: None,
+            checksum: legacy_checksum(DEVICE_CODE, &serial_number, &issued_at),
+            serial_number,
+        };
+        edit(&mut data);
+        let json = serde_json::to_string(&data).unwrap();
+        let (ciphertext, nonce) = encrypt(json.as_bytes(), &LEGACY_LICENSE_KEY, &[]).unwrap();
+        format!("{}:{}", STANDARD.encode(ciphertext), STANDARD.encode(nonce)).into_bytes()
+    }
+
+    fn allow_legacy() -> VerifyOptions {
+        VerifyOptions {
+            allow_legacy: true,
+            ..VerifyOptions::default()
+        }
+    }
+
+    #[test]
+    fn legacy_licenses_require_reissue_unless_allowed() {
+        let license = legacy_license(|_| {});
+        let error = verify(&license, DEVICE_CODE, &verifier_keys()).unwrap_err();
+        assert_eq!(error.kind, VerifyErrorKind::ReissueRequired);
+
+        let verified =
+            verify_license(&license, DEVICE_CODE, &verifier_keys(), &allow_legacy()).unwrap();
+        assert_eq!(verified.format_version, LEGACY_FORMAT_VERSION);
+        assert_eq!(verified.checksum_kind, ChecksumKind::LegacySha256);
+        assert_eq!(verified.auth_data.device_code, DEVICE_CODE);
+    }
+
+    #[test]
+    fn allowed_legacy_licenses_are_still_checked() {
+        let license = legacy_license(|_| {});
+        let error = verify_license(
+            &license,
+            OTHER_DEVICE_CODE,
+            &verifier_keys(),
+            &allow_legacy(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, VerifyErrorKind::DeviceMismatch);
+
+        let tampered = legacy_license(|d| d.serial_number.push('2'));
+        let error =
+            verify_license(&tampered, DEVICE_CODE, &verifier_keys(), &allow_legacy()).unwrap_err();
+        assert_eq!(error.kind, VerifyErrorKind::Tampered);
+
+        // 旧版校验和不覆盖新字段，不能借旧格式签发高级版本
+        let upgraded = legacy_license(|d| d.edition = Edition::Enterprise);
+        let error =
+            verify_license(&upgraded, DEVICE_CODE, &verifier_keys(), &allow_legacy()).unwrap_err();
+        assert_eq!(error.kind, VerifyErrorKind::Tampered);
+    }
+
+    #[test]
+    fn verify_reads_the_local_fingerprint_when_the_license_has_one() {
+        let mut data = auth_data();
+        data.fingerprint = Some(local_fingerprint().unwrap());
+        let license = seal(data, &signing_key());
+
+        let verified = verify(&license, DEVICE_CODE, &verifier_keys()).unwrap();
+        assert!(verified.fingerprint.is_some());
+        let verified = verify_license(
+            &license,
+            DEVICE_CODE,
+            &verifier_keys(),
+            &VerifyOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            verified.fingerprint_match.unwrap().drifted,
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn fingerprint_of_another_machine_is_rejected() {
+        let mut data = auth_data();
+        data.fingerprint = Some(DeviceFingerprint {
+            threshold_percent: 60,
+            components: vec![FingerprintComponent::new(
+                "machine_id",
+                3,
+                "another machine",
+            )],
+        });
+        let license = seal(data, &signing_key());
+
+        let error = verify(&license, DEVICE_CODE, &verifier_keys()).unwrap_err();
+        assert_eq!(error.kind, VerifyErrorKind::FingerprintMismatch);
+
+        // 签发端自检显式跳过指纹比对
+        let options = VerifyOptions {
+            skip_fingerprint: true,
+            ..VerifyOptions::default()
+        };
+        let verified = verify_license(&license, DEVICE_CODE, &verifier_keys(), &options).unwrap();
+        assert!(verified.fingerprint_match.is_none());
+    }
+
+    #[test]
+    fn signed_product_id_is_required() {
+        let mut data = auth_data();
+        data.product_id = None;
+        let license = seal(data, &signing_key());
+        let error = verify(&license, DEVICE_CODE, &verifier_keys()).unwrap_err();
+        assert_eq!(error.kind, VerifyErrorKind::Tampered);
+        assert!(error.message.contains("产品标识"), "{}", error.message);
+    }
+
+    #[test]
+    fn licenses_without_fingerprint_skip_the_comparison() {
+        let license = seal(auth_data(), &signing_key());
+        let verified = verify_license(
+            &license,
+            DEVICE_CODE,
+            &verifier_keys(),
+            &VerifyOptions::default(),
+        )
+        .unwrap();
+        assert!(verified.fingerprint_match.is_none());
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::en_de_crypt::CryptoError;
+
+// 校验失败的类别，前端据此给出不同的处理提示
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyErrorKind {
+    MissingFile,    // 授权文件或设备码文件不存在/无法读取
+    KeyUnavailable, // 本机没有可用的校验密钥
+    BadEncoding,    // 授权文件格式或编码无效
+    WrongLength,    // 定长字段长度错误
+    WrongKey,       // 授权文件不是用本机已知的密钥签发的
+    Tampered,       // 密文、签名或 MAC 校验失败
+    DeviceMismatch, // 授权文件与设备码不匹配
+    // 附加数据认证失败：授权文件被挪到其他设备/产品下，或已被篡改
+    BindingMismatch,
+    NotYetValid,
+    Expired,
+    FingerprintMismatch, // 当前设备的硬件指纹匹配度低于授权要求
+    InvalidDeviceCode,   // 设备码格式或校验位错误
+    ReissueRequired,     // 旧版两段式授权文件，不再支持，需重新签发
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VerifyError {
+    pub kind: VerifyErrorKind,
+    pub message: String,
+}
+
+impl VerifyError {
+    pub(crate) fn new(kind: VerifyErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<CryptoError> for VerifyError {
+    fn from(e: CryptoError) -> Self {
+        let kind = match e {
+            CryptoError::BadEncoding(_) | CryptoError::RandomUnavailable(_) => {
+                VerifyErrorKind::BadEncoding
+            }
+            CryptoError::WrongLength { .. } => VerifyErrorKind::WrongLength,
+            CryptoError::AuthenticationFailed => VerifyErrorKind::Tampered,
+        };
+        Self::new(kind, e.to_string())
+    }
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use std::net::TcpListener;
+use std::time::Duration;
+
+use authorization_lib::{
+    parse_network_address, validate_pairing_code, AdbClient, AdbDeviceState, TransferProgress,
+};
+use common::{FakeAdbServer, PAIRING_CODE, UNREACHABLE_HOST};
+
+const SERIAL: &str = "2G0YC5ZF9J0123";
+
+fn server_with_device() -> (FakeAdbServer, AdbClient) {
+    let server = FakeAdbServer::start();
+    server.add_device(SERIAL, "Quest_3");
+    let client = AdbClient::new(server.addr.clone()).with_timeout(Duration::from_secs(5));
+    (server, client)
+}
+
+#[test]
+fn reports_server_version() {
+    let (_server, client) = server_with_device();
+    assert!(client.is_server_running());
+    assert_eq!(client.server_version().unwrap(), 41);
+}
+
+#[test]
+fn lists_devices_with_details() {
+    let (server, client) = server_with_device();
+    server
+        .devices_listing
+        .lock()
+        .unwrap()
+        .push_str("1WMHH000000000 unauthorized usb:1-2 transport_id:2\n");
+
+    let devices = client.devices().unwrap();
+    assert_eq!(devices.len(), 2);
+    assert_eq!(devices[0].serial, SERIAL);
+    assert_eq!(devices[0].state, AdbDeviceState::Device);
+    assert_eq!(devices[0].model.as_deref(), Some("Quest_3"));
+    assert_eq!(devices[0].transport_id, Some(1));
+    assert_eq!(devices[1].state, AdbDeviceState::Unauthorized);
+    assert!(devices[1].guidance.is_some());
+}
+
+#[test]
+fn push_then_pull_round_trips_with_progress() {
+    let (server, client) = server_with_device();
+    let dir = std::env::temp_dir().join(format!("adb_client_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let local = dir.join("license.lic");
+    let content: Vec<u8> = (0..200u8).collect();
+    std::fs::write(&local, &content).unwrap();
+
+    let remote = "/sdcard/Android/data/app/files/license.lic";
+    let mut pushed = Vec::new();
+    let sent = client
+        .push(Some(SERIAL), &local, remote, &mut |p| pushed.push(p))
+        .unwrap();
+    assert_eq!(sent, 200);
+    assert_eq!(server.file(SERIAL, remote).unwrap(), content);
+    assert_eq!(
+        pushed.last(),
+        Some(&TransferProgress {
+            transferred: 200,
+            total: Some(200)
+        })
+    );
+
+    let stat = client.stat(Some(SERIAL), remote).unwrap().unwrap();
+    assert!(stat.is_file());
+    assert_eq!(stat.size, 200);
+
+    let pulled_path = dir.join("pulled.lic");
+    let mut pulled = Vec::new();
+    let received = client
+        .pull(Some(SERIAL), remote, &pulled_path, &mut |p| pulled.push(p))
+        .unwrap();
+    assert_eq!(received, 200);
+    assert_eq!(std::fs::read(&pulled_path).unwrap(), content);
+    assert!(pulled.len() > 1, "进度应按数据块多次上报");
+    assert_eq!(pulled.last().unwrap().transferred, 200);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missing_remote_file_is_reported() {
+    let (_server, client) = server_with_device();
+    assert_eq!(client.stat(Some(SERIAL), "/sdcard/missing").unwrap(), None);
+
+    let local = std::env::temp_dir().join(format!("adb_client_missing_{}", std::process::id()));
+    let err = client
+        .pull(Some(SERIAL), "/sdcard/missing", &local, &mut |_| {})
+        .unwrap_err();
+    assert!(err.contains("/sdcard/missing"), "{}", err);
+    assert!(!local.exists());
+}
+
+#[test]
+fn push_failure_carries_device_message() {
+    let (_server, client) = server_with_device();
+    let local = std::env::temp_dir().join(format!("adb_client_ro_{}", std::process::id()));
+    std::fs::write(&local, b"license").unwrap();
+    let err = client
+        .push(Some(SERIAL), &local, "/readonly/license.lic", &mut |_| {})
+        .unwrap_err();
+    assert!(err.contains("Read-only file system"), "{}", err);
+    std::fs::remove_file(&local).unwrap();
+}
+
+#[test]
+fn unknown_serial_is_rejected_by_server() {
+    let (_server, client) = server_with_device();
+    let err = client.shell(Some("no-such-device"), "id").unwrap_err();
+    assert!(err.contains("not found"), "{}", err);
+}
+
+#[test]
+fn runs_shell_commands() {
+    let (server, client) = server_with_device();
+    server.set_shell_output(SERIAL, "getprop ro.product.model", "Quest 3\n");
+    assert_eq!(
+        client
+            .shell(Some(SERIAL), "getprop ro.product.model")
+            .unwrap(),
+        "Quest 3\n"
+    );
+}
+
+#[test]
+fn connection_refused_is_an_error() {
+    let addr = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+    let client = AdbClient::new(addr).with_timeout(Duration::from_secs(1));
+    assert!(!client.is_server_running());
+    assert!(client
+        .devices()
+        .unwrap_err()
+        .contains("无法连接 ADB 服务器"));
+}
+
+#[test]
+fn pairs_connects_and_disconnects_wifi_device() {
+    let (_server, client) = server_with_device();
+    let address = "192.168.1.20:5555";
+
+    assert!(client.pair_device("192.168.1.20:37123", "000000").is_err());
+    let paired = client
+        .pair_device("192.168.1.20:37123", PAIRING_CODE)
+        .unwrap();
+    assert!(paired.starts_with("Successfully paired"));
+
+    assert_eq!(
+        client.connect_device(address).unwrap(),
+        format!("connected to {}", address)
+    );
+    assert!(client
+        .connect_device(address)
+        .unwrap()
+        .starts_with("already connected"));
+    let devices = client.devices().unwrap();
+    let wifi = devices.iter().find(|d| d.serial == address).unwrap();
+    assert!(wifi.network && wifi.is_ready());
+    assert!(!devices.iter().find(|d| d.serial == SERIAL).unwrap().network);
+
+    client.disconnect_device(address).unwrap();
+    assert!(client.devices().unwrap().iter().all(|d| !d.network));
+    assert!(client.disconnect_device(address).is_err());
+
+    let refused = client
+        .connect_device(&format!("{}:5555", UNREACHABLE_HOST))
+        .unwrap_err();
+    assert!(refused.starts_with("failed to connect"));
+}
+
+#[test]
+fn lists_mdns_services() {
+    let (server, client) = server_with_device();
+    *server.mdns_listing.lock().unwrap() =
+        "adb-1WMHH-abc\t_adb-tls-pairing._tcp.\t192.168.1.20:37123\n\
+         adb-1WMHH-abc\t_adb-tls-connect._tcp.\t192.168.1.20:41000\n"
+            .to_string();
+
+    let services = client.mdns_services().unwrap();
+    assert_eq!(services.len(), 2);
+    assert!(services[0].is_pairing());
+    assert!(!services[1].is_pairing());
+    assert_eq!(services[1].address, "192.168.1.20:41000");
+}
+
+#[test]
+fn validates_wireless_addresses_and_pairing_codes() {
+    assert_eq!(
+        parse_network_address(" 192.168.1.20 ", Some(5555)).unwrap(),
+        "192.168.1.20:5555"
+    );
+    assert_eq!(
+        parse_network_address("quest.local:41000", None).unwrap(),
+        "quest.local:41000"
+    );
+    assert_eq!(
+        parse_network_address("[fe80::1]:5555", None).unwrap(),
+        "[fe80::1]:5555"
+    );
+    assert!(parse_network_address("192.168.1.20", None).is_err());
+    assert!(parse_network_address("192.168.1.20:0", None).is_err());
+    assert!(parse_network_address("192.168.1.20:abc", Some(5555)).is_err());
+    assert!(parse_network_address(":5555", None).is_err());
+    assert!(parse_network_address("fe80::1", Some(5555)).is_err());
+
+    assert_eq!(validate_pairing_code(" 123456 ").unwrap(), "123456");
+    assert!(validate_pairing_code("12345").is_err());
+    assert!(validate_pairing_code("12345a").is_err());
+}
+
+#[test]
+fn renames_remote_files_with_quoted_paths() {
+    let (server, client) = server_with_device();
+    server.set_shell_output(
+        SERIAL,
+        "mv -f '/sdcard/it'\\''s.tmp' '/sdcard/it'\\''s' && echo __adb_ok__",
+        "__adb_ok__\n",
+    );
+
+    client
+        .rename(Some(SERIAL), "/sdcard/it's.tmp", "/sdcard/it's")
+        .unwrap();
+    let err = client
+        .rename(Some(SERIAL), "/sdcard/missing", "/sdcard/target")
+        .unwrap_err();
+    assert!(err.contains("重命名"));
+}
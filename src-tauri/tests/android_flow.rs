@@ -0,0 +1,497 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use authorization_lib::{
+    authorize_android_devices, authorize_device, push_file, AdbBackend, AdbDevice,
+    AndroidAuthOptions, AndroidAuthReporter, DeviceCode, MockAdbBackend, MockDevice, PushMode,
+    TransferProgress, DEFAULT_ANDROID_CLIENT_DIRS,
+};
+
+const DATA_DIR: &str = DEFAULT_ANDROID_CLIENT_DIRS[0];
+const MEDIA_DIR: &str = DEFAULT_ANDROID_CLIENT_DIRS[1];
+
+#[derive(Default)]
+struct RecordingReporter {
+    logs: Mutex<Vec<(String, String)>>,
+    transfers: Mutex<Vec<(String, String, TransferProgress)>>,
+}
+
+impl AndroidAuthReporter for RecordingReporter {
+    fn log(&self, level: &str, message: String) {
+        self.logs.lock().unwrap().push((level.to_string(), message));
+    }
+
+    fn transfer(&self, serial: &str, file_name: &str, progress: TransferProgress) {
+        self.transfers
+            .lock()
+            .unwrap()
+            .push((serial.to_string(), file_name.to_string(), progress));
+    }
+}
+
+impl RecordingReporter {
+    fn has_log(&self, level: &str, needle: &str) -> bool {
+        self.logs
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(l, message)| l == level && message.contains(needle))
+    }
+}
+
+// 测试用签发函数：授权内容即设备码本身，便于核对推送结果
+fn fake_issue(device_code: &DeviceCode) -> Result<Vec<u8>, String> {
+    Ok(format!("license-for-{}", device_code.as_str()).into_bytes())
+}
+
+// 测试用校验函数：回读的内容必须是为该设备码签发的
+fn fake_verify(license_bytes: &[u8], device_code: &DeviceCode) -> Result<(), String> {
+    if fake_issue(device_code)? == license_bytes {
+        Ok(())
+    } else {
+        Err("授权文件与设备码不匹配".to_string())
+    }
+}
+
+fn path(dir: &str, file: &str) -> String {
+    format!("{}{}", dir, file)
+}
+
+fn headset(serial: &str, dir: &str, device_code: &str) -> MockDevice {
+    MockDevice::from_listing(&format!(
+        "{} device usb:1-1 product:eureka model:Quest_3 device:eureka transport_id:1",
+        serial
+    ))
+    .with_file(&path(dir, "device_code.bin"), device_code.as_bytes())
+}
+
+fn batch() -> AndroidAuthOptions {
+    AndroidAuthOptions {
+        batch_mode: true,
+        ..AndroidAuthOptions::default()
+    }
+}
+
+#[test]
+fn batch_mode_authorizes_every_ready_headset() {
+    let backend = MockAdbBackend::new(vec![
+        headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c"),
+        headset("HEADSET2", MEDIA_DIR, "0123456789abcdef\n"),
+        MockDevice::from_listing("HEADSET3 unauthorized usb:1-3 transport_id:3"),
+    ]);
+    let reporter = RecordingReporter::default();
+
+    let outcomes =
+        authorize_android_devices(&backend, &batch(), &fake_issue, &fake_verify, &reporter)
+            .unwrap();
+
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes.iter().all(|outcome| outcome.succeeded()));
+    assert_eq!(outcomes[0].remote_dir.as_deref(), Some(DATA_DIR));
+    assert_eq!(
+        outcomes[0].device_code.as_deref(),
+        Some("FB95-DCE2-CC77-799C-9")
+    );
+    assert_eq!(outcomes[1].remote_dir.as_deref(), Some(MEDIA_DIR));
+    assert_eq!(
+        backend
+            .file("HEADSET1", &path(DATA_DIR, "license.lic"))
+            .unwrap(),
+        b"license-for-fb95dce2cc77799c"
+    );
+    assert_eq!(
+        backend
+            .file("HEADSET2", &path(MEDIA_DIR, "license.lic"))
+            .unwrap(),
+        b"license-for-0123456789abcdef"
+    );
+    assert!(reporter.has_log("warn", "HEADSET3"));
+    assert!(reporter
+        .transfers
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(serial, file, _)| serial == "HEADSET2" && file == "license.lic"));
+}
+
+#[test]
+fn single_mode_only_processes_the_first_ready_headset() {
+    let backend = MockAdbBackend::new(vec![
+        MockDevice::from_listing("OFFLINE1 offline transport_id:1"),
+        headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c"),
+        headset("HEADSET2", DATA_DIR, "0123456789abcdef"),
+    ]);
+    let reporter = RecordingReporter::default();
+
+    let outcomes = authorize_android_devices(
+        &backend,
+        &AndroidAuthOptions::default(),
+        &fake_issue,
+        &fake_verify,
+        &reporter,
+    )
+    .unwrap();
+
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].serial, "HEADSET1");
+    assert!(backend
+        .file("HEADSET2", &path(DATA_DIR, "license.lic"))
+        .is_none());
+}
+
+#[test]
+fn headset_fails_when_no_client_directory_works() {
+    let backend = MockAdbBackend::new(vec![
+        headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c").with_read_only_dir(DATA_DIR),
+        MockDevice::from_listing("EMPTY1 device model:Quest_2 transport_id:2"),
+        headset("BADCODE", DATA_DIR, "not a device code"),
+    ]);
+    let reporter = RecordingReporter::default();
+
+    let outcomes =
+        authorize_android_devices(&backend, &batch(), &fake_issue, &fake_verify, &reporter)
+            .unwrap();
+
+    assert_eq!(outcomes.len(), 3);
+    assert!(outcomes.iter().all(|outcome| !outcome.succeeded()));
+    assert!(reporter.has_log("warn", "Read-only file system"));
+    assert!(reporter.has_log("warn", "设备码无效"));
+    assert!(outcomes[0].summary().contains("授权失败"));
+}
+
+#[test]
+fn issuing_errors_are_reported_per_headset() {
+    let backend = MockAdbBackend::new(vec![headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c")]);
+    let reporter = RecordingReporter::default();
+    let locked_keystore =
+        |_: &DeviceCode| -> Result<Vec<u8>, String> { Err("密钥库未解锁".to_string()) };
+
+    let outcomes = authorize_android_devices(
+        &backend,
+        &batch(),
+        &locked_keystore,
+        &fake_verify,
+        &reporter,
+    )
+    .unwrap();
+
+    assert!(!outcomes[0].succeeded());
+    assert!(reporter.has_log("warn", "密钥库未解锁"));
+}
+
+// 记录同时进行中的拉取数量，拉取时稍作等待让并发有机会发生
+struct SlowBackend {
+    inner: MockAdbBackend,
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl AdbBackend for SlowBackend {
+    fn devices(&self) -> Result<Vec<AdbDevice>, String> {
+        self.inner.devices()
+    }
+
+    fn pull(
+        &self,
+        serial: &str,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<Vec<u8>, String> {
+        let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(30));
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.inner.pull(serial, remote_path, progress)
+    }
+
+    fn push(
+        &self,
+        serial: &str,
+        data: &[u8],
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<(), String> {
+        self.inner.push(serial, data, remote_path, progress)
+    }
+
+    fn shell(&self, serial: &str, command: &str) -> Result<String, String> {
+        self.inner.shell(serial, command)
+    }
+
+    fn rename(&self, serial: &str, from: &str, to: &str) -> Result<(), String> {
+        self.inner.rename(serial, from, to)
+    }
+}
+
+#[test]
+fn batch_mode_respects_the_concurrency_limit() {
+    let headsets = (0..10)
+        .map(|i| {
+            headset(
+                &format!("HEADSET{:02}", i),
+                DATA_DIR,
+                &format!("{:016x}", i),
+            )
+        })
+        .collect();
+    let backend = SlowBackend {
+        inner: MockAdbBackend::new(headsets),
+        in_flight: AtomicUsize::new(0),
+        peak: AtomicUsize::new(0),
+    };
+    let options = AndroidAuthOptions {
+        max_concurrency: 3,
+        ..batch()
+    };
+
+    let outcomes = authorize_android_devices(
+        &backend,
+        &options,
+        &fake_issue,
+        &fake_verify,
+        &RecordingReporter::default(),
+    )
+    .unwrap();
+
+    let peak = backend.peak.load(Ordering::SeqCst);
+    assert!((2..=3).contains(&peak), "peak concurrency {}", peak);
+    // 结果按设备顺序返回，且每台设备拿到的是自己的授权
+    for (i, outcome) in outcomes.iter().enumerate() {
+        assert_eq!(outcome.serial, format!("HEADSET{:02}", i));
+        assert!(outcome.succeeded());
+        assert_eq!(
+            backend
+                .inner
+                .file(&outcome.serial, &path(DATA_DIR, "license.lic"))
+                .unwrap(),
+            format!("license-for-{:016x}", i).into_bytes()
+        );
+    }
+}
+
+#[test]
+fn no_usable_headset_is_an_error() {
+    let reporter = RecordingReporter::default();
+    let err = authorize_android_devices(
+        &MockAdbBackend::default(),
+        &batch(),
+        &fake_issue,
+        &fake_verify,
+        &reporter,
+    )
+    .unwrap_err();
+    assert!(err.contains("未检测到设备"));
+
+    let backend = MockAdbBackend::new(vec![MockDevice::from_listing(
+        "HEADSET1 unauthorized usb:1-1 transport_id:1",
+    )]);
+    let err = authorize_android_devices(&backend, &batch(), &fake_issue, &fake_verify, &reporter)
+        .unwrap_err();
+    assert!(err.contains("均不可用"));
+    assert!(reporter.has_log("warn", "允许 USB 调试"));
+}
+
+// 推送后文件被改写 (例如客户端启动时覆盖了授权文件)，回读内容与签发内容不一致
+struct CorruptingBackend {
+    inner: MockAdbBackend,
+}
+
+impl AdbBackend for CorruptingBackend {
+    fn devices(&self) -> Result<Vec<AdbDevice>, String> {
+        self.inner.devices()
+    }
+
+    fn pull(
+        &self,
+        serial: &str,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<Vec<u8>, String> {
+        self.inner.pull(serial, remote_path, progress)
+    }
+
+    fn push(
+        &self,
+        serial: &str,
+        data: &[u8],
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<(), String> {
+        self.inner
+            .push(serial, &data[..data.len() / 2], remote_path, progress)
+    }
+
+    fn shell(&self, serial: &str, command: &str) -> Result<String, String> {
+        self.inner.shell(serial, command)
+    }
+
+    fn rename(&self, serial: &str, from: &str, to: &str) -> Result<(), String> {
+        self.inner.rename(serial, from, to)
+    }
+}
+
+#[test]
+fn pushed_license_is_read_back_and_verified() {
+    let backend = CorruptingBackend {
+        inner: MockAdbBackend::new(vec![headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c")]),
+    };
+    let reporter = RecordingReporter::default();
+    let device = backend.devices().unwrap().remove(0);
+    let options = AndroidAuthOptions::default();
+
+    let outcome = authorize_device(
+        &backend,
+        &options,
+        &fake_issue,
+        &fake_verify,
+        &reporter,
+        &device,
+    );
+    assert!(outcome.unverified() && !outcome.succeeded());
+    assert_eq!(outcome.remote_dir.as_deref(), Some(DATA_DIR));
+    assert!(outcome
+        .verification_error
+        .as_deref()
+        .unwrap()
+        .contains("SHA-256"));
+    assert!(outcome.summary().contains("已推送但未通过校验"));
+
+    // 内容一致但校验不通过 (例如签发时用了客户端不认识的密钥)
+    let backend = MockAdbBackend::new(vec![headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c")]);
+    let wrong_key = |_: &[u8], _: &DeviceCode| -> Result<(), String> {
+        Err("授权文件不是用本机已知的密钥签发的".to_string())
+    };
+    let outcome = authorize_device(
+        &backend,
+        &options,
+        &fake_issue,
+        &wrong_key,
+        &reporter,
+        &device,
+    );
+    assert!(outcome.unverified());
+    assert!(outcome
+        .verification_error
+        .as_deref()
+        .unwrap()
+        .contains("本机已知的密钥"));
+    assert!(reporter.has_log("error", "未通过校验"));
+}
+
+// 传输到一半时断开：已写入的部分留在设备上，随后报错
+struct CablePulledBackend {
+    inner: MockAdbBackend,
+}
+
+impl AdbBackend for CablePulledBackend {
+    fn devices(&self) -> Result<Vec<AdbDevice>, String> {
+        self.inner.devices()
+    }
+
+    fn pull(
+        &self,
+        serial: &str,
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<Vec<u8>, String> {
+        self.inner.pull(serial, remote_path, progress)
+    }
+
+    fn push(
+        &self,
+        serial: &str,
+        data: &[u8],
+        remote_path: &str,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<(), String> {
+        self.inner
+            .push(serial, &data[..data.len() / 2], remote_path, progress)?;
+        Err("device 'HEADSET1' not found".to_string())
+    }
+
+    fn shell(&self, serial: &str, command: &str) -> Result<String, String> {
+        self.inner.shell(serial, command)
+    }
+
+    fn rename(&self, serial: &str, from: &str, to: &str) -> Result<(), String> {
+        self.inner.rename(serial, from, to)
+    }
+}
+
+#[test]
+fn atomic_push_never_leaves_a_half_written_license() {
+    let license = path(DATA_DIR, "license.lic");
+    let backend = CablePulledBackend {
+        inner: MockAdbBackend::new(vec![
+            headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c").with_file(&license, b"old license")
+        ]),
+    };
+
+    let err = push_file(
+        &backend,
+        "HEADSET1",
+        b"new license content",
+        DATA_DIR,
+        "license.lic",
+        PushMode::Atomic,
+        &mut |_| {},
+    )
+    .unwrap_err();
+    assert!(err.contains("not found"));
+    assert_eq!(
+        backend.inner.file("HEADSET1", &license).unwrap(),
+        b"old license"
+    );
+
+    push_file(
+        &backend,
+        "HEADSET1",
+        b"new license content",
+        DATA_DIR,
+        "license.lic",
+        PushMode::Direct,
+        &mut |_| {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        backend.inner.file("HEADSET1", &license).unwrap(),
+        b"new licen"
+    );
+}
+
+#[test]
+fn push_file_uses_the_explicit_file_name() {
+    let backend = MockAdbBackend::new(vec![headset("HEADSET1", DATA_DIR, "fb95dce2cc77799c")]);
+
+    let remote = push_file(
+        &backend,
+        "HEADSET1",
+        b"{}",
+        "/sdcard/Download",
+        "config.json",
+        PushMode::Atomic,
+        &mut |_| {},
+    )
+    .unwrap();
+    assert_eq!(remote, "/sdcard/Download/config.json");
+    assert_eq!(backend.file("HEADSET1", &remote).unwrap(), b"{}");
+    // 临时文件已被重命名，不会残留
+    assert_eq!(
+        backend.file_paths("HEADSET1"),
+        vec![remote.clone(), path(DATA_DIR, "device_code.bin")]
+    );
+    for name in ["", "..", "a/b"] {
+        assert!(push_file(
+            &backend,
+            "HEADSET1",
+            b"{}",
+            DATA_DIR,
+            name,
+            PushMode::Atomic,
+            &mut |_| {}
+        )
+        .is_err());
+    }
+}
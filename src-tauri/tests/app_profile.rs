@@ -0,0 +1,133 @@
+use authorization_lib::{
+    authorize_android_devices, AndroidAuthOptions, AndroidAuthReporter, AppProfiles, DeviceCode,
+    MockAdbBackend, MockDevice, APP_PROFILES_FILE_NAME, DEFAULT_ANDROID_CLIENT_DIRS,
+    DEFAULT_APP_PROFILE_ID,
+};
+
+const PROFILES: &str = r#"{
+    "default_profile": "trainer",
+    "profiles": [
+        {
+            "id": "trainer",
+            "name": "实训客户端",
+            "package_name": "edu.bupt.vr.trainer",
+            "remote_dirs": [
+                "/storage/emulated/0/Android/data/{package}/files/",
+                "/sdcard/Download/trainer/"
+            ],
+            "device_code_file": "trainer_code.txt",
+            "license_file": "trainer.lic",
+            "key_id": "20240101-a1b2c3d4"
+        },
+        {
+            "id": "viewer",
+            "name": "展示客户端",
+            "package_name": "edu.bupt.vr.viewer",
+            "remote_dirs": ["/storage/emulated/0/Android/media/{package}/"]
+        }
+    ]
+}"#;
+
+struct SilentReporter;
+
+impl AndroidAuthReporter for SilentReporter {
+    fn log(&self, _level: &str, _message: String) {}
+}
+
+#[test]
+fn builtin_profile_matches_the_alvr_client() {
+    let dir = std::env::temp_dir().join(format!("app_profile_missing_{}", std::process::id()));
+    let profiles = AppProfiles::load(&dir).unwrap();
+    let profile = profiles.get(None).unwrap();
+
+    assert_eq!(profile.id, DEFAULT_APP_PROFILE_ID);
+    assert_eq!(profile.remote_dirs(), DEFAULT_ANDROID_CLIENT_DIRS);
+    let options = profile.auth_options();
+    let defaults = AndroidAuthOptions::default();
+    assert_eq!(options.device_code_file, defaults.device_code_file);
+    assert_eq!(options.license_file, defaults.license_file);
+    assert!(profile.key_id.is_none());
+}
+
+#[test]
+fn loads_profiles_from_the_config_dir() {
+    let dir = std::env::temp_dir().join(format!("app_profile_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(APP_PROFILES_FILE_NAME), PROFILES).unwrap();
+
+    let profiles = AppProfiles::load(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let trainer = profiles.get(None).unwrap();
+    assert_eq!(trainer.id, "trainer");
+    assert_eq!(trainer.key_id.as_deref(), Some("20240101-a1b2c3d4"));
+    assert_eq!(
+        trainer.remote_dirs(),
+        vec![
+            "/storage/emulated/0/Android/data/edu.bupt.vr.trainer/files/",
+            "/sdcard/Download/trainer/",
+        ]
+    );
+
+    // 未写文件名时使用默认文件名
+    let viewer = profiles.get(Some("viewer")).unwrap();
+    assert_eq!(viewer.device_code_file, "device_code.bin");
+    assert_eq!(viewer.license_file, "license.lic");
+    assert!(profiles.get(Some("unknown")).is_err());
+}
+
+#[test]
+fn rejects_invalid_profiles() {
+    let invalid = [
+        PROFILES.replace(
+            "\"default_profile\": \"trainer\"",
+            "\"default_profile\": \"missing\"",
+        ),
+        PROFILES.replace("\"id\": \"viewer\"", "\"id\": \"trainer\""),
+        PROFILES.replace("edu.bupt.vr.viewer", "viewer app"),
+        PROFILES.replace("\"/sdcard/Download/trainer/\"", "\"sdcard/Download\""),
+        PROFILES.replace("trainer.lic", "licenses/trainer.lic"),
+        PROFILES.replace("trainer.lic", "trainer_code.txt"),
+        r#"{"default_profile": "alvr", "profiles": []}"#.to_string(),
+    ];
+    for json in &invalid {
+        assert!(AppProfiles::from_json(json).is_err(), "{}", json);
+    }
+    assert!(AppProfiles::from_json(PROFILES).is_ok());
+}
+
+#[test]
+fn authorization_uses_the_profile_file_names() {
+    let profiles = AppProfiles::from_json(PROFILES).unwrap();
+    let trainer = profiles.get(Some("trainer")).unwrap();
+    let backend = MockAdbBackend::new(vec![MockDevice::from_listing(
+        "HEADSET1 device usb:1-1 model:Quest_3 transport_id:1",
+    )
+    .with_file(
+        "/sdcard/Download/trainer/trainer_code.txt",
+        b"fb95dce2cc77799c",
+    )]);
+    let issue = |device_code: &DeviceCode| Ok(device_code.as_str().as_bytes().to_vec());
+    let verify = |_: &[u8], _: &DeviceCode| Ok(());
+
+    let outcomes = authorize_android_devices(
+        &backend,
+        &trainer.auth_options(),
+        &issue,
+        &verify,
+        &SilentReporter,
+    )
+    .unwrap();
+
+    assert!(outcomes[0].succeeded());
+    assert_eq!(
+        outcomes[0].remote_dir.as_deref(),
+        Some("/sdcard/Download/trainer/")
+    );
+    assert_eq!(
+        backend
+            .file("HEADSET1", "/sdcard/Download/trainer/trainer.lic")
+            .unwrap(),
+        b"fb95dce2cc77799c"
+    );
+}
@@ -0,0 +1,267 @@
+// 集成测试共用的假 ADB 服务器：在本机随机端口上实现主机协议与 sync 子协议的最小子集
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct FakeDevice {
+    pub files: HashMap<String, Vec<u8>>,
+    pub shell_outputs: HashMap<String, String>,
+}
+
+#[derive(Clone)]
+pub struct FakeAdbServer {
+    pub addr: String,
+    pub devices_listing: Arc<Mutex<String>>,
+    pub devices: Arc<Mutex<HashMap<String, FakeDevice>>>,
+    pub mdns_listing: Arc<Mutex<String>>,
+}
+
+// 假服务器只接受这个配对码；连接 UNREACHABLE_HOST 上的地址总是失败
+pub const PAIRING_CODE: &str = "123456";
+pub const UNREACHABLE_HOST: &str = "10.0.0.99";
+
+impl FakeAdbServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = Self {
+            addr: listener.local_addr().unwrap().to_string(),
+            devices_listing: Arc::default(),
+            devices: Arc::default(),
+            mdns_listing: Arc::default(),
+        };
+        let handler = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let handler = handler.clone();
+                thread::spawn(move || {
+                    let _ = handler.handle(stream);
+                });
+            }
+        });
+        server
+    }
+
+    // 添加一台在线设备，并同步更新 host:devices-l 的输出
+    pub fn add_device(&self, serial: &str, model: &str) {
+        self.devices
+            .lock()
+            .unwrap()
+            .insert(serial.to_string(), FakeDevice::default());
+        self.devices_listing.lock().unwrap().push_str(&format!(
+            "{} device usb:1-1 product:eureka model:{} device:eureka transport_id:1\n",
+            serial, model
+        ));
+    }
+
+    pub fn put_file(&self, serial: &str, path: &str, content: &[u8]) {
+        self.devices
+            .lock()
+            .unwrap()
+            .get_mut(serial)
+            .unwrap()
+            .files
+            .insert(path.to_string(), content.to_vec());
+    }
+
+    pub fn file(&self, serial: &str, path: &str) -> Option<Vec<u8>> {
+        self.devices.lock().unwrap()[serial]
+            .files
+            .get(path)
+            .cloned()
+    }
+
+    pub fn set_shell_output(&self, serial: &str, command: &str, output: &str) {
+        self.devices
+            .lock()
+            .unwrap()
+            .get_mut(serial)
+            .unwrap()
+            .shell_outputs
+            .insert(command.to_string(), output.to_string());
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut serial: Option<String> = None;
+        loop {
+            let request = read_request(&mut stream)?;
+            if request == "host:version" {
+                stream.write_all(b"OKAY")?;
+                return write_prefixed(&mut stream, "0029");
+            } else if request == "host:devices-l" {
+                stream.write_all(b"OKAY")?;
+                let listing = self.devices_listing.lock().unwrap().clone();
+                return write_prefixed(&mut stream, &listing);
+            } else if request == "host:track-devices-l" {
+                // 先发送当前列表，之后列表变化时再发送完整列表，直到客户端断开
+                stream.write_all(b"OKAY")?;
+                let mut sent: Option<String> = None;
+                loop {
+                    let listing = self.devices_listing.lock().unwrap().clone();
+                    if sent.as_ref() != Some(&listing) {
+                        write_prefixed(&mut stream, &listing)?;
+                        sent = Some(listing);
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                }
+            } else if let Some(address) = request.strip_prefix("host:connect:") {
+                // 与真实服务器一致：连接失败也返回 OKAY，结果写在消息里
+                stream.write_all(b"OKAY")?;
+                let message = if address.starts_with(UNREACHABLE_HOST) {
+                    format!("failed to connect to '{}': Connection refused", address)
+                } else if self.devices.lock().unwrap().contains_key(address) {
+                    format!("already connected to {}", address)
+                } else {
+                    self.devices
+                        .lock()
+                        .unwrap()
+                        .insert(address.to_string(), FakeDevice::default());
+                    self.devices_listing.lock().unwrap().push_str(&format!(
+                        "{} device product:eureka model:Quest_3 device:eureka transport_id:9\n",
+                        address
+                    ));
+                    format!("connected to {}", address)
+                };
+                return write_prefixed(&mut stream, &message);
+            } else if let Some(address) = request.strip_prefix("host:disconnect:") {
+                if self.devices.lock().unwrap().remove(address).is_none() {
+                    stream.write_all(b"FAIL")?;
+                    return write_prefixed(&mut stream, &format!("no such device '{}'", address));
+                }
+                let mut listing = self.devices_listing.lock().unwrap();
+                *listing = listing
+                    .lines()
+                    .filter(|line| !line.starts_with(&format!("{} ", address)))
+                    .map(|line| format!("{}\n", line))
+                    .collect();
+                stream.write_all(b"OKAY")?;
+                return write_prefixed(&mut stream, &format!("disconnected {}", address));
+            } else if let Some(pairing) = request.strip_prefix("host:pair:") {
+                let (code, address) = pairing.split_once(':').unwrap();
+                stream.write_all(b"OKAY")?;
+                let message = if code == PAIRING_CODE {
+                    format!("Successfully paired to {} [guid=adb-FAKE-abc123]", address)
+                } else {
+                    "Failed: Wrong password or connection was dropped.".to_string()
+                };
+                return write_prefixed(&mut stream, &message);
+            } else if request == "host:mdns:services" {
+                stream.write_all(b"OKAY")?;
+                let listing = self.mdns_listing.lock().unwrap().clone();
+                return write_prefixed(&mut stream, &listing);
+            } else if request == "host:kill" {
+                return stream.write_all(b"OKAY");
+            } else if let Some(target) = request.strip_prefix("host:transport:") {
+                if !self.devices.lock().unwrap().contains_key(target) {
+                    stream.write_all(b"FAIL")?;
+                    return write_prefixed(&mut stream, &format!("device '{}' not found", target));
+                }
+                serial = Some(target.to_string());
+                stream.write_all(b"OKAY")?;
+            } else if let Some(command) = request.strip_prefix("shell:") {
+                let output = serial
+                    .as_ref()
+                    .and_then(|s| {
+                        self.devices.lock().unwrap()[s]
+                            .shell_outputs
+                            .get(command)
+                            .cloned()
+                    })
+                    .unwrap_or_default();
+                stream.write_all(b"OKAY")?;
+                return stream.write_all(output.as_bytes());
+            } else if request == "sync:" {
+                stream.write_all(b"OKAY")?;
+                return self.handle_sync(&mut stream, serial.as_deref().unwrap_or_default());
+            } else {
+                stream.write_all(b"FAIL")?;
+                return write_prefixed(&mut stream, &format!("unknown request {}", request));
+            }
+        }
+    }
+
+    fn handle_sync(&self, stream: &mut TcpStream, serial: &str) -> std::io::Result<()> {
+        loop {
+            let (id, len) = read_sync_header(stream)?;
+            let mut data = vec![0u8; len as usize];
+            if &id != b"QUIT" {
+                stream.read_exact(&mut data)?;
+            }
+            let path = String::from_utf8_lossy(&data).into_owned();
+            match &id {
+                b"STAT" => {
+                    let size = self.file(serial, &path).map(|f| f.len() as u32);
+                    stream.write_all(b"STAT")?;
+                    let mode: u32 = if size.is_some() { 0o100644 } else { 0 };
+                    for value in [mode, size.unwrap_or(0), size.map_or(0, |_| 1_700_000_000)] {
+                        stream.write_all(&value.to_le_bytes())?;
+                    }
+                }
+                b"RECV" => match self.file(serial, &path) {
+                    Some(content) => {
+                        // 故意拆成小块，覆盖多个 DATA 块的情况
+                        for chunk in content.chunks(7) {
+                            write_sync(stream, b"DATA", chunk)?;
+                        }
+                        write_sync(stream, b"DONE", &[])?;
+                    }
+                    None => write_sync(stream, b"FAIL", b"No such file or directory")?,
+                },
+                b"SEND" => {
+                    let (remote, _mode) = path.rsplit_once(',').unwrap();
+                    let mut content = Vec::new();
+                    loop {
+                        let (id, len) = read_sync_header(stream)?;
+                        if &id == b"DONE" {
+                            break;
+                        }
+                        let mut chunk = vec![0u8; len as usize];
+                        stream.read_exact(&mut chunk)?;
+                        content.extend_from_slice(&chunk);
+                    }
+                    if remote.starts_with("/readonly/") {
+                        write_sync(stream, b"FAIL", b"Read-only file system")?;
+                    } else {
+                        self.put_file(serial, remote, &content);
+                        write_sync(stream, b"OKAY", &[])?;
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = usize::from_str_radix(std::str::from_utf8(&len).unwrap(), 16).unwrap();
+    let mut request = vec![0u8; len];
+    stream.read_exact(&mut request)?;
+    Ok(String::from_utf8(request).unwrap())
+}
+
+fn write_prefixed(stream: &mut TcpStream, payload: &str) -> std::io::Result<()> {
+    stream.write_all(format!("{:04x}{}", payload.len(), payload).as_bytes())
+}
+
+fn read_sync_header(stream: &mut TcpStream) -> std::io::Result<([u8; 4], u32)> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    Ok((
+        header[..4].try_into().unwrap(),
+        u32::from_le_bytes(header[4..].try_into().unwrap()),
+    ))
+}
+
+fn write_sync(stream: &mut TcpStream, id: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    stream.write_all(id)?;
+    stream.write_all(&(data.len() as u32).to_le_bytes())?;
+    stream.write_all(data)
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use authorization_lib::{
+    AdbClient, AdbDevice, AutoAuthorizePool, DeviceEvent, DeviceTracker, MockDevice,
+};
+use common::FakeAdbServer;
+
+fn device(line: &str) -> AdbDevice {
+    MockDevice::from_listing(line).info
+}
+
+#[test]
+fn tracker_reports_connects_state_changes_and_disconnects() {
+    let mut tracker = DeviceTracker::new();
+    let unauthorized = device("HEADSET1 unauthorized usb:1-1 transport_id:1");
+    let ready = device("HEADSET1 device usb:1-1 model:Quest_3 transport_id:1");
+    let other = device("HEADSET2 device usb:1-2 model:Quest_3 transport_id:2");
+
+    assert_eq!(
+        tracker.update(vec![unauthorized.clone()]),
+        vec![DeviceEvent::Connected {
+            device: unauthorized
+        }]
+    );
+    // 同一份列表不产生事件
+    assert_eq!(tracker.update(vec![ready.clone()]).len(), 1);
+    assert!(tracker.update(vec![ready.clone()]).is_empty());
+    assert_eq!(
+        tracker.update(vec![other.clone()]),
+        vec![
+            DeviceEvent::Disconnected {
+                serial: "HEADSET1".to_string()
+            },
+            DeviceEvent::Connected { device: other }
+        ]
+    );
+    assert_eq!(tracker.devices().len(), 1);
+}
+
+#[test]
+fn each_headset_is_auto_authorized_once() {
+    let mut tracker = DeviceTracker::new();
+    let unauthorized = device("HEADSET1 unauthorized usb:1-1 transport_id:1");
+    let ready = device("HEADSET1 device usb:1-1 transport_id:1");
+
+    // 未允许调试时不授权，允许后 (状态变化) 授权
+    let events = tracker.update(vec![unauthorized]);
+    assert!(tracker.take_auto_authorize(&events).is_empty());
+    let events = tracker.update(vec![ready.clone()]);
+    assert_eq!(tracker.take_auto_authorize(&events), vec![ready.clone()]);
+
+    // 拔出后重新插入不会再次授权
+    let events = tracker.update(Vec::new());
+    assert!(tracker.take_auto_authorize(&events).is_empty());
+    let events = tracker.update(vec![ready]);
+    assert_eq!(events.len(), 1);
+    assert!(tracker.take_auto_authorize(&events).is_empty());
+}
+
+#[test]
+fn track_devices_streams_listing_changes_until_stopped() {
+    let server = FakeAdbServer::start();
+    let client = AdbClient::new(server.addr.clone()).with_timeout(Duration::from_secs(5));
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        let watcher = scope.spawn(|| {
+            let mut tracker = DeviceTracker::new();
+            client.track_devices(&stop, &mut |devices| {
+                for event in tracker.update(devices) {
+                    sender.send(event).unwrap();
+                }
+            })
+        });
+
+        server.add_device("HEADSET1", "Quest_3");
+        match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            DeviceEvent::Connected { device } => assert_eq!(device.serial, "HEADSET1"),
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        server.devices_listing.lock().unwrap().clear();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            DeviceEvent::Disconnected {
+                serial: "HEADSET1".to_string()
+            }
+        );
+
+        stop.store(true, Ordering::Relaxed);
+        assert_eq!(watcher.join().unwrap(), Ok(()));
+    });
+}
+
+#[test]
+fn auto_authorize_pool_respects_the_concurrency_limit() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let pool = {
+        let (in_flight, peak, handled) = (in_flight.clone(), peak.clone(), handled.clone());
+        AutoAuthorizePool::new(3, move |device: AdbDevice| {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            handled.lock().unwrap().push(device.serial);
+        })
+    };
+
+    // 一次插入 40 台设备
+    for i in 0..40 {
+        pool.submit(device(&format!(
+            "HEADSET{:02} device usb:1-{} transport_id:{}",
+            i, i, i
+        )));
+    }
+    pool.finish();
+
+    let peak = peak.load(Ordering::SeqCst);
+    assert!((2..=3).contains(&peak), "peak concurrency {}", peak);
+    let mut handled = handled.lock().unwrap().clone();
+    handled.sort();
+    assert_eq!(handled.len(), 40);
+    assert_eq!(handled[0], "HEADSET00");
+}
@@ -10,12 +10,50 @@ const refreshDevicesBtn = document.getElementById('refreshDevices');
 const deviceListDiv = document.getElementById('deviceList');
 const authorizeAndroidBtn = document.getElementById('authorizeAndroid');
 const batchModeCheckbox = document.getElementById('batchModeCheckbox');
+const maxConcurrencyInput = document.getElementById('maxConcurrencyInput');
+const appProfileSelect = document.getElementById('appProfileSelect');
+const watchDevicesCheckbox = document.getElementById('watchDevicesCheckbox');
+const autoAuthorizeCheckbox = document.getElementById('autoAuthorizeCheckbox');
+const pairAddressInput = document.getElementById('pairAddressInput');
+const pairingCodeInput = document.getElementById('pairingCodeInput');
+const pairDeviceBtn = document.getElementById('pairDeviceBtn');
+const connectAddressInput = document.getElementById('connectAddressInput');
+const connectDeviceBtn = document.getElementById('connectDeviceBtn');
+const disconnectDeviceBtn = document.getElementById('disconnectDeviceBtn');
+const refreshNetworkDevicesBtn = document.getElementById('refreshNetworkDevicesBtn');
+const networkDeviceListSpan = document.getElementById('networkDeviceList');
 
 const selectAppDirBtn = document.getElementById('selectAppDirBtn');
 const appDirPathDisplay = document.getElementById('appDirPathDisplay');
 const authorizeWindowsAppBtn = document.getElementById('authorizeWindowsAppBtn');
 const windowsAuthResultP = document.getElementById('windowsAuthResult');
 
+const manualDeviceCodeInput = document.getElementById('manualDeviceCodeInput');
+const selectOutputDirBtn = document.getElementById('selectOutputDirBtn');
+const outputDirDisplay = document.getElementById('outputDirDisplay');
+const issueFromCodeBtn = document.getElementById('issueFromCodeBtn');
+const manualIssueResultP = document.getElementById('manualIssueResult');
+const selectRequestFileBtn = document.getElementById('selectRequestFileBtn');
+const requestSummaryDisplay = document.getElementById('requestSummaryDisplay');
+const issueFromRequestBtn = document.getElementById('issueFromRequestBtn');
+const requestIssueResultP = document.getElementById('requestIssueResult');
+
+const keystoreStatusDisplay = document.getElementById('keystoreStatusDisplay');
+const keystorePassphraseInput = document.getElementById('keystorePassphrase');
+const unlockKeystoreBtn = document.getElementById('unlockKeystoreBtn');
+const lockKeystoreBtn = document.getElementById('lockKeystoreBtn');
+const rotateKeyBtn = document.getElementById('rotateKeyBtn');
+
+const notBeforeInput = document.getElementById('notBeforeInput');
+const expiresAtInput = document.getElementById('expiresAtInput');
+const clockSkewInput = document.getElementById('clockSkewInput');
+const editionSelect = document.getElementById('editionSelect');
+const productIdInput = document.getElementById('productIdInput');
+const featureMultiplayerCheckbox = document.getElementById('featureMultiplayer');
+const featureRecordingCheckbox = document.getElementById('featureRecording');
+const maxSessionsInput = document.getElementById('maxSessionsInput');
+const fingerprintThresholdInput = document.getElementById('fingerprintThresholdInput');
+
 // New Status/Progress Bar Elements
 const statusText = document.getElementById('statusText');
 const progressBar = document.getElementById('progressBar');
@@ -27,6 +65,8 @@ const closeBtn = document.getElementById('titlebar-close');
 const appWindow = getCurrentWindow();
 
 let selectedAppDir = null;
+let selectedOutputDir = null;
+let selectedRequestFile = null;
 
 // --- Logging and Status Updates ---
 function updateStatus(message, isError = false) {
@@ -52,6 +92,15 @@ listen('log_message', (event) => { //
     console.log("[Rust]: " + event.payload);
 });
 
+// ADB 传输进度：{ device_id, file_name, transferred, total }
+listen('adb_transfer_progress', (event) => {
+    const { device_id, file_name, transferred, total } = event.payload;
+    if (total) {
+        updateProgress(Math.round(transferred * 100 / total));
+    }
+    updateStatus(`设备 ${device_id}: 正在传输 ${file_name} (${transferred}${total ? ' / ' + total : ''} 字节)`);
+});
+
 
 // --- Progress Bar Functions ---
 function showProgress() {
@@ -74,6 +123,71 @@ function updateProgress(value) { // value from 0 to 100
 }
 
 
+// --- License Terms ---
+// datetime-local 的值为本地时间，转换为 UTC ISO 字符串后交给后端
+function toUtcIsoString(localValue) {
+    return localValue ? new Date(localValue).toISOString() : null;
+}
+
+function collectFeatures() {
+    const features = {};
+    if (featureMultiplayerCheckbox.checked) {
+        features.multiplayer = true;
+    }
+    if (featureRecordingCheckbox.checked) {
+        features.recording = true;
+    }
+    const maxSessions = parseInt(maxSessionsInput.value, 10);
+    if (!Number.isNaN(maxSessions)) {
+        features.max_sessions = maxSessions;
+    }
+    return features;
+}
+
+function collectLicenseTerms() {
+    return {
+        not_before: toUtcIsoString(notBeforeInput.value),
+        expires_at: toUtcIsoString(expiresAtInput.value),
+        edition: editionSelect.value,
+        features: collectFeatures(),
+        product_id: productIdInput.value.trim() || null,
+        fingerprint_threshold_percent: collectFingerprintThreshold(),
+    };
+}
+
+function collectFingerprintThreshold() {
+    const value = parseInt(fingerprintThresholdInput.value, 10);
+    return Number.isNaN(value) ? null : value;
+}
+
+function collectClockSkewSecs() {
+    const value = parseInt(clockSkewInput.value, 10);
+    return Number.isNaN(value) ? null : value;
+}
+
+// --- Verification Errors ---
+// 与后端 VerifyErrorKind 对应的处理提示
+const VERIFY_ERROR_HINTS = {
+    missing_file: '授权文件或设备码文件缺失，请确认应用目录。',
+    key_unavailable: '本机没有可用的校验密钥，请先在密钥管理中创建密钥库。',
+    bad_encoding: '授权文件格式无效或已损坏，请重新签发。',
+    wrong_length: '授权文件字段长度错误，文件可能已损坏。',
+    wrong_key: '授权文件不是由本机密钥签发的。',
+    tampered: '授权文件校验失败，可能已被篡改。',
+    device_mismatch: '授权文件与当前设备码不匹配。',
+    binding_mismatch: '授权文件与当前设备码或产品标识不匹配，请确认签发设置中的产品标识。',
+    not_yet_valid: '授权尚未到生效时间。',
+    expired: '授权已过期。',
+    fingerprint_mismatch: '当前设备硬件变化过多，与授权记录的硬件指纹不符，请重新签发。',
+    invalid_device_code: '设备码格式或校验位错误，请核对设备码。',
+    reissue_required: '这是旧版授权文件，已不再支持，请为该设备重新签发授权。',
+};
+
+function describeVerifyError(error) {
+    const hint = VERIFY_ERROR_HINTS[error.kind] || '';
+    return hint ? `${hint} (${error.message})` : error.message;
+}
+
 // --- Tab Management ---
 function openTab(evt, tabName) {
     var i, tabcontent, tablinks;
@@ -113,6 +227,12 @@ document.addEventListener('DOMContentLoaded', async () => {
         appDirPathDisplay.textContent = '未选择 (请手动选择)';
     }
     updateWindowsAuthorizeAppButtonState();
+    try {
+        renderKeystoreStatus(await invoke('get_keystore_status'));
+    } catch (e) {
+        keystoreStatusDisplay.textContent = '获取失败: ' + e;
+    }
+    await loadAppProfiles();
     updateStatus('前端脚本已加载。应用准备就绪。');
 
     if (minimizeBtn) {
@@ -128,26 +248,181 @@ document.addEventListener('DOMContentLoaded', async () => {
 
 
 // --- Android Tab Logic ---
+// 应用配置来自配置目录下的 app_profiles.json，未配置时只有内置的 ALVR 客户端
+async function loadAppProfiles() {
+    try {
+        const { default_profile, profiles } = await invoke('list_app_profiles');
+        appProfileSelect.replaceChildren(...profiles.map(profile => {
+            const option = document.createElement('option');
+            option.value = profile.id;
+            option.textContent = `${profile.name} (${profile.package_name})`;
+            option.selected = profile.id === default_profile;
+            return option;
+        }));
+    } catch (e) {
+        updateStatus('读取应用配置失败: ' + e, true);
+    }
+}
+
+if (appProfileSelect) {
+    // 监听中切换应用或并发数时按新配置重新开启
+    for (const input of [appProfileSelect, maxConcurrencyInput]) {
+        input.addEventListener('change', () => {
+            if (watchDevicesCheckbox.checked) {
+                updateDeviceWatcher();
+            }
+        });
+    }
+}
+
+const ADB_STATE_LABELS = {
+    device: '可用',
+    unauthorized: '未允许 USB 调试',
+    authorizing: '正在确认调试授权',
+    offline: '离线',
+    connecting: '连接中',
+    recovery: '恢复模式',
+    sideload: 'sideload 模式',
+    bootloader: 'bootloader 模式',
+    no_permissions: '无 USB 访问权限',
+    unknown: '未知状态',
+};
+
+function describeAdbDevice(device) {
+    const serial = device.network ? `Wi-Fi ${device.serial}` : device.serial;
+    const name = device.model ? `${device.model} (${serial})` : serial;
+    const state = ADB_STATE_LABELS[device.state] || device.state_text;
+    return device.guidance ? `${name} - ${state}: ${device.guidance}` : `${name} - ${state}`;
+}
+
+async function refreshDeviceList() {
+    updateStatus('正在刷新 Android 设备列表...');
+    showProgress(); // Show progress for this async operation
+    updateProgress(30); // Example: indeterminate start
+    try {
+        const devices = await invoke('list_adb_devices');
+        deviceListDiv.textContent = devices.length > 0 ? devices.map(describeAdbDevice).join('\n') : '无设备连接';
+        const unready = devices.filter(device => device.state !== 'device');
+        if (unready.length > 0) {
+            updateStatus(`有 ${unready.length} 台设备暂不可用: ${unready[0].guidance}`, true);
+        } else {
+            updateStatus('设备列表刷新成功: ' + (devices.length > 0 ? devices.map(d => d.serial).join(', ') : '无设备连接'));
+        }
+        updateProgress(100);
+    } catch (error) {
+        deviceListDiv.textContent = '获取失败';
+        updateStatus('获取设备列表错误: ' + error, true);
+        updateProgress(100); // Still complete the "progress" even if error
+    } finally {
+        setTimeout(hideProgress, 500); // Hide after a short delay
+    }
+}
+
 if (refreshDevicesBtn) {
-    refreshDevicesBtn.addEventListener('click', async () => {
-        updateStatus('正在刷新 Android 设备列表...');
-        showProgress(); // Show progress for this async operation
-        updateProgress(30); // Example: indeterminate start
-        try {
-            const devices = await invoke('list_adb_devices');
-            deviceListDiv.textContent = (devices.length > 0 ? devices.join(', ') : '无设备连接');
-            updateStatus('设备列表刷新成功: ' + (devices.length > 0 ? devices.join(', ') : '无设备连接'));
-            updateProgress(100);
-        } catch (error) {
-            deviceListDiv.textContent = '获取失败';
-            updateStatus('获取设备列表错误: ' + error, true);
-            updateProgress(100); // Still complete the "progress" even if error
-        } finally {
-            setTimeout(hideProgress, 500); // Hide after a short delay
+    refreshDevicesBtn.addEventListener('click', refreshDeviceList);
+}
+
+// --- Wireless ADB ---
+async function refreshNetworkDevices() {
+    try {
+        const { devices, services } = await invoke('list_network_devices');
+        const lines = devices.map(describeAdbDevice);
+        // mDNS 发现的设备：等待配对的可直接填入配对地址，可连接的填入连接地址
+        for (const service of services) {
+            const connected = devices.some(device => device.serial === service.address || device.serial === service.name);
+            if (!connected) {
+                lines.push(`${service.name} @ ${service.address} - ${service.service_type.startsWith('_adb-tls-pairing') ? '等待配对' : '可连接'}`);
+            }
+        }
+        networkDeviceListSpan.textContent = lines.length > 0 ? lines.join('\n') : '无';
+    } catch (error) {
+        networkDeviceListSpan.textContent = '获取失败';
+        updateStatus('获取无线设备错误: ' + error, true);
+    }
+}
+
+async function runWirelessCommand(button, command, args, pendingText) {
+    button.disabled = true;
+    updateStatus(pendingText);
+    try {
+        const message = await invoke(command, args);
+        updateStatus(message);
+        await refreshDeviceList();
+        await refreshNetworkDevices();
+    } catch (error) {
+        updateStatus(error, true);
+    } finally {
+        button.disabled = false;
+    }
+}
+
+if (pairDeviceBtn) {
+    pairDeviceBtn.addEventListener('click', () => runWirelessCommand(pairDeviceBtn, 'pair_adb_device', {
+        address: pairAddressInput.value,
+        pairingCode: pairingCodeInput.value,
+    }, '正在配对...'));
+    connectDeviceBtn.addEventListener('click', () => runWirelessCommand(connectDeviceBtn, 'connect_adb_device', {
+        address: connectAddressInput.value,
+    }, '正在连接...'));
+    disconnectDeviceBtn.addEventListener('click', () => runWirelessCommand(disconnectDeviceBtn, 'disconnect_adb_device', {
+        address: connectAddressInput.value,
+    }, '正在断开...'));
+    refreshNetworkDevicesBtn.addEventListener('click', refreshNetworkDevices);
+}
+
+// --- Device Watcher ---
+async function updateDeviceWatcher() {
+    try {
+        if (watchDevicesCheckbox.checked) {
+            await invoke('start_device_watcher', {
+                autoAuthorize: autoAuthorizeCheckbox.checked,
+                licenseTerms: collectLicenseTerms(),
+                profileId: appProfileSelect.value || null,
+                maxConcurrency: parseInt(maxConcurrencyInput.value, 10) || null,
+            });
+            updateStatus(autoAuthorizeCheckbox.checked ? '正在监听设备连接，新设备将自动授权' : '正在监听设备连接');
+        } else {
+            await invoke('stop_device_watcher');
+            updateStatus('已停止监听设备连接');
+        }
+    } catch (error) {
+        watchDevicesCheckbox.checked = false;
+        updateStatus('设备监听开启失败: ' + error, true);
+    }
+}
+
+if (watchDevicesCheckbox) {
+    watchDevicesCheckbox.addEventListener('change', updateDeviceWatcher);
+    // 监听中切换自动授权时按新设置重新开启
+    autoAuthorizeCheckbox.addEventListener('change', () => {
+        if (watchDevicesCheckbox.checked) {
+            updateDeviceWatcher();
         }
     });
 }
 
+// { kind: 'connected' | 'state_changed' | 'disconnected', device?, serial? }
+listen('adb_device_event', async (event) => {
+    const { kind, device, serial } = event.payload;
+    await refreshDeviceList();
+    if (kind === 'disconnected') {
+        updateStatus(`设备已断开: ${serial}`);
+    } else {
+        updateStatus(`设备已连接: ${describeAdbDevice(device)}`, device.state !== 'device');
+    }
+});
+
+listen('adb_auto_authorized', (event) => {
+    const outcome = event.payload;
+    if (outcome.error) {
+        updateStatus(`自动授权失败: ${outcome.label} - ${outcome.error}`, true);
+    } else if (outcome.verification_error) {
+        updateStatus(`授权文件已推送但未通过校验: ${outcome.label} - ${outcome.verification_error}`, true);
+    } else {
+        updateStatus(`自动授权成功: ${outcome.label}`);
+    }
+});
+
 if (authorizeAndroidBtn) {
     authorizeAndroidBtn.addEventListener("click", async () => {
         const batchMode = batchModeCheckbox.checked;
@@ -162,7 +437,12 @@ if (authorizeAndroidBtn) {
             await new Promise(resolve => setTimeout(resolve, 500)); // Simulate work
             updateProgress(50);
 
-            const result = await invoke("process_android_authorization", { batchMode }); //
+            const result = await invoke("process_android_authorization", {
+                batchMode,
+                licenseTerms: collectLicenseTerms(),
+                maxConcurrency: parseInt(maxConcurrencyInput.value, 10) || null,
+                profileId: appProfileSelect.value || null,
+            });
             updateStatus("Android 授权操作完成!"); // Simpler status update
             console.log("Android 授权结果: \n" + result); // Detailed result to console
             alert("Android 设备授权操作完成！详细信息请查看控制台日志。");
@@ -227,7 +507,9 @@ if (authorizeWindowsAppBtn) {
             updateProgress(30);
 
             const result = await invoke('authorize_windows_application', { //
-                applicationPathStr: selectedAppDir
+                applicationPathStr: selectedAppDir,
+                licenseTerms: collectLicenseTerms(),
+                clockSkewSecs: collectClockSkewSecs(),
             });
 
             updateProgress(80);
@@ -236,10 +518,19 @@ if (authorizeWindowsAppBtn) {
             console.log(`校验状态: ${result.verification_status}`); //
 
             windowsAuthResultP.textContent = `授权: ${result.authorization_message}. \n校验: ${result.verification_status}`; //
+            if (result.verification_error) {
+                windowsAuthResultP.textContent = `授权: ${result.authorization_message}. \n校验失败: ${describeVerifyError(result.verification_error)}`;
+            }
             if (result.verification_details) {
-                console.log(`校验详情: 设备码=${result.verification_details.device_code}, 序列号=${result.verification_details.serial_number}, 时间=${result.verification_details.issued_at}`); //
+                console.log(`校验详情: 设备码=${result.verification_details.device_code}, 序列号=${result.verification_details.serial_number}, 时间=${result.verification_details.issued_at}, 到期=${result.verification_details.expires_at ?? '永久'}`); //
                 windowsAuthResultP.textContent += ` (设备码: ${result.verification_details.device_code})`; //
             }
+            if (result.fingerprint_match && result.fingerprint_match.drifted.length > 0) {
+                windowsAuthResultP.textContent += `\n硬件指纹部分变化: ${result.fingerprint_match.drifted.join(', ')}`;
+            }
+            if (result.entitlements) {
+                console.log(`授权版本: ${result.entitlements.edition}, 功能: ${JSON.stringify(result.entitlements.features)}`);
+            }
 
             if (result.verification_status.includes("通过")) { //
                 alert('Windows 应用授权和校验成功！详细信息请查看控制台日志和结果区域。');
@@ -260,4 +551,170 @@ if (authorizeWindowsAppBtn) {
 }
 
 // Initial state for the button
-updateWindowsAuthorizeAppButtonState();
\ No newline at end of file
+updateWindowsAuthorizeAppButtonState();
+
+// --- Manual Issue Tab Logic ---
+function updateIssueFromCodeButtonState() {
+    issueFromCodeBtn.disabled = !selectedOutputDir || !manualDeviceCodeInput.value.trim();
+    issueFromRequestBtn.disabled = !selectedOutputDir || !selectedRequestFile;
+}
+
+function describeIssuedLicense(result) {
+    let text = `已为设备码 ${result.device_code} 生成授权: ${result.license_path}`;
+    if (result.auth_data.request_nonce) {
+        text += `\n申请编号: ${result.auth_data.request_nonce}`;
+    }
+    if (result.verification_error) {
+        text += `\n自检失败: ${describeVerifyError(result.verification_error)}`;
+    }
+    return text;
+}
+
+if (selectOutputDirBtn) {
+    selectOutputDirBtn.addEventListener('click', async () => {
+        try {
+            const dir = await open({ directory: true, multiple: false, title: "选择授权文件输出目录" });
+            if (dir) {
+                selectedOutputDir = dir;
+                outputDirDisplay.textContent = dir;
+            }
+            updateIssueFromCodeButtonState();
+        } catch (error) {
+            updateStatus('选择目录错误: ' + error, true);
+        }
+    });
+    manualDeviceCodeInput.addEventListener('input', updateIssueFromCodeButtonState);
+}
+
+if (issueFromCodeBtn) {
+    issueFromCodeBtn.addEventListener('click', async () => {
+        issueFromCodeBtn.disabled = true;
+        updateStatus('正在按设备码签发授权...');
+        try {
+            const result = await invoke('issue_license_for_device_code', {
+                deviceCode: manualDeviceCodeInput.value,
+                outputPath: selectedOutputDir,
+                licenseTerms: collectLicenseTerms(),
+            });
+            manualIssueResultP.textContent = describeIssuedLicense(result);
+            if (result.verification_error) {
+                updateStatus('授权已生成，但自检失败。', true);
+            } else {
+                updateStatus('授权文件已生成，请发送给客户。');
+            }
+        } catch (error) {
+            manualIssueResultP.textContent = '签发失败: ' + error;
+            updateStatus('按设备码签发失败: ' + error, true);
+        } finally {
+            updateIssueFromCodeButtonState();
+        }
+    });
+}
+
+if (selectRequestFileBtn) {
+    selectRequestFileBtn.addEventListener('click', async () => {
+        try {
+            const file = await open({
+                multiple: false,
+                title: "选择授权申请文件",
+                filters: [{ name: '授权申请', extensions: ['req'] }],
+            });
+            if (!file) {
+                return;
+            }
+            selectedRequestFile = null;
+            const request = await invoke('read_license_request', { requestPath: file });
+            selectedRequestFile = file;
+            const reference = request.customer_reference ? `，客户备注 ${request.customer_reference}` : '';
+            requestSummaryDisplay.textContent =
+                `设备码 ${request.device_code}，产品 ${request.product_id}，客户端版本 ${request.client_version}${reference}，申请编号 ${request.nonce}`;
+        } catch (error) {
+            requestSummaryDisplay.textContent = '无效的申请文件: ' + error;
+            updateStatus('读取授权申请文件失败: ' + error, true);
+        } finally {
+            updateIssueFromCodeButtonState();
+        }
+    });
+}
+
+if (issueFromRequestBtn) {
+    issueFromRequestBtn.addEventListener('click', async () => {
+        issueFromRequestBtn.disabled = true;
+        updateStatus('正在按授权申请签发授权...');
+        try {
+            const result = await invoke('issue_license_from_request', {
+                requestPath: selectedRequestFile,
+                outputPath: selectedOutputDir,
+                licenseTerms: collectLicenseTerms(),
+            });
+            requestIssueResultP.textContent = describeIssuedLicense(result);
+            if (result.verification_error) {
+                updateStatus('授权已生成，但自检失败。', true);
+            } else {
+                updateStatus('授权文件已生成，请发送给客户。');
+            }
+        } catch (error) {
+            requestIssueResultP.textContent = '签发失败: ' + error;
+            updateStatus('按授权申请签发失败: ' + error, true);
+        } finally {
+            updateIssueFromCodeButtonState();
+        }
+    });
+}
+
+// --- Keystore Tab Logic ---
+function renderKeystoreStatus(status) {
+    if (!status.exists) {
+        keystoreStatusDisplay.textContent = '尚未创建 (输入口令后解锁即可新建)';
+    } else if (!status.unlocked) {
+        keystoreStatusDisplay.textContent = `已锁定 (共 ${status.key_ids.length} 把密钥)`;
+    } else {
+        keystoreStatusDisplay.textContent = `已解锁，当前签名密钥: ${status.active_key_id} (共 ${status.key_ids.length} 把密钥)`;
+    }
+    lockKeystoreBtn.disabled = !status.unlocked;
+    rotateKeyBtn.disabled = !status.unlocked;
+}
+
+if (unlockKeystoreBtn) {
+    unlockKeystoreBtn.addEventListener('click', async () => {
+        const passphrase = keystorePassphraseInput.value;
+        if (!passphrase) {
+            updateStatus('请输入密钥库口令。', true);
+            return;
+        }
+        unlockKeystoreBtn.disabled = true;
+        updateStatus('正在解锁密钥库...');
+        try {
+            renderKeystoreStatus(await invoke('unlock_keystore', { passphrase }));
+            keystorePassphraseInput.value = '';
+            updateStatus('密钥库已解锁。');
+        } catch (error) {
+            updateStatus('解锁密钥库失败: ' + error, true);
+        } finally {
+            unlockKeystoreBtn.disabled = false;
+        }
+    });
+}
+
+if (lockKeystoreBtn) {
+    lockKeystoreBtn.addEventListener('click', async () => {
+        try {
+            renderKeystoreStatus(await invoke('lock_keystore'));
+            updateStatus('密钥库已锁定。');
+        } catch (error) {
+            updateStatus('锁定密钥库失败: ' + error, true);
+        }
+    });
+}
+
+if (rotateKeyBtn) {
+    rotateKeyBtn.addEventListener('click', async () => {
+        try {
+            const status = await invoke('rotate_signing_key');
+            renderKeystoreStatus(status);
+            updateStatus(`已轮换签名密钥，新密钥: ${status.active_key_id}`);
+        } catch (error) {
+            updateStatus('轮换签名密钥失败: ' + error, true);
+        }
+    });
+}